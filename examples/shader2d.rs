@@ -14,6 +14,7 @@ struct ExampleUniform {
     time: f32,
     trans: Mat4,
 }
+visgen_graph::uniform_layout!(ExampleUniform { color, time, trans });
 
 struct Model {
     target: Shader2DTarget<ExampleUniform>,
@@ -33,15 +34,9 @@ fn model(app: &App) -> Model {
         color: [1.0, 0.8, 0.7, 1.0],
         trans: Mat4::IDENTITY,
     };
-    let target = Shader2DTarget::new(
-        device,
-        texture_size,
-        &vert,
-        &frag,
-        &FULL_SCREEN_QUAD,
-        &[],
-        uniform,
-    );
+    let target = ShaderTargetBuilder::new(&vert, &frag, &FULL_SCREEN_QUAD, &[], uniform)
+        .build(device, texture_size)
+        .unwrap_or_else(|e| panic!("{}", e));
 
     Model { target }
 }
@@ -54,10 +49,13 @@ fn update(app: &App, model: &mut Model, _update: Update) {
     };
     let window = app.main_window();
     let device = window.device();
-    model.target.begin(device);
-    model.target.set_uniforms(device, uniform);
-    model.target.render_pass();
-    model.target.end(&window);
+    let ce_desc = wgpu::CommandEncoderDescriptor {
+        label: Some("shader2d"),
+    };
+    let mut encoder = device.create_command_encoder(&ce_desc);
+    model.target.set_uniforms(window.queue(), uniform);
+    model.target.render_pass(device, &mut encoder, Vec::new());
+    window.queue().submit(Some(encoder.finish()));
 }
 
 fn view(_app: &App, model: &Model, frame: Frame) {
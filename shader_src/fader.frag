@@ -23,6 +23,7 @@ layout(set = 0, binding = 0) uniform Data {
 layout(set =0, binding = 1) uniform texture2D tex1;
 layout(set =0, binding = 2) uniform texture2D tex2;
 layout(set =0, binding = 3) uniform sampler samp;
+layout(set =0, binding = 4) uniform sampler samp2;
 
 
 void main() {
@@ -38,7 +39,7 @@ void main() {
 
     vec4 c0 = vec4(1.0, 1.0, 1.0, 1.0);
     vec4 c1 = vec4(texture(sampler2D(tex1, samp), (v_pos+vec2(1,1))*0.5 ));
-    vec4 c2 = vec4(texture(sampler2D(tex2, samp), (v_pos+vec2(1,1))*0.5 ));
+    vec4 c2 = vec4(texture(sampler2D(tex2, samp2), (v_pos+vec2(1,1))*0.5 ));
     vec4 ci1 = c0 - c1;
     vec4 ci2 = c0 - c2;
 
@@ -0,0 +1,25 @@
+#version 450
+
+layout(location = 0) out vec4 f_color;
+layout(location = 1) in vec2 v_pos;
+
+layout(set = 0, binding = 0) uniform Data {
+    float decay;
+    float zoom;
+    float rotate;
+} uniforms;
+
+layout(set =0, binding = 1) uniform texture2D tex1;
+layout(set =0, binding = 2) uniform sampler samp;
+
+
+void main() {
+    // zoom and rotate around the center of the texture
+    float c = cos(uniforms.rotate);
+    float s = sin(uniforms.rotate);
+    vec2 pos = mat2(c, s, -s, c) * v_pos / uniforms.zoom;
+    vec2 uv = (pos + vec2(1, 1)) * 0.5;
+
+    // outside of the previous frame depends on the address mode of the feedback port
+    f_color = texture(sampler2D(tex1, samp), uv) * uniforms.decay;
+}
@@ -16,6 +16,8 @@ layout(set =0, binding = 1) uniform texture2D tex1;
 layout(set =0, binding = 2) uniform texture2D tex2;
 layout(set =0, binding = 3) uniform texture2D mask;
 layout(set =0, binding = 4) uniform sampler samp;
+layout(set =0, binding = 5) uniform sampler samp2;
+layout(set =0, binding = 6) uniform sampler samp3;
 
 
 void main() {
@@ -24,8 +26,8 @@ void main() {
 
     vec4 c0 = vec4(1.0, 1.0, 1.0, 1.0);
     vec4 c1 = vec4(texture(sampler2D(tex1, samp), (v_pos+vec2(1,1))*0.5 ));
-    vec4 c2 = vec4(texture(sampler2D(tex2, samp), (v_pos+vec2(1,1))*0.5 ));
-    vec4 m = vec4(texture(sampler2D(mask, samp), (v_pos+vec2(1,1))*0.5 ));
+    vec4 c2 = vec4(texture(sampler2D(tex2, samp2), (v_pos+vec2(1,1))*0.5 ));
+    vec4 m = vec4(texture(sampler2D(mask, samp3), (v_pos+vec2(1,1))*0.5 ));
 
     f_color = c1*m + c2*(c0-m);
 }
\ No newline at end of file
@@ -0,0 +1,49 @@
+#version 450
+
+layout(location = 0) out vec4 f_color;
+layout(location = 1) in vec2 v_pos;
+
+layout(set = 0, binding = 0) uniform Data {
+    float exposure;
+    float gamma;
+    int tonemap;
+    int linear;
+} uniforms;
+
+layout(set =0, binding = 1) uniform texture2D tex1;
+layout(set =0, binding = 2) uniform sampler samp;
+
+vec3 srgb_to_linear(vec3 c) {
+    return mix(c / 12.92, pow((c + 0.055) / 1.055, vec3(2.4)), step(0.04045, c));
+}
+
+// fit of the ACES filmic curve by Krzysztof Narkowicz
+vec3 aces(vec3 c) {
+    return (c * (2.51 * c + 0.03)) / (c * (2.43 * c + 0.59) + 0.14);
+}
+
+void main() {
+    vec4 color = texture(sampler2D(tex1, samp), (v_pos+vec2(1,1))*0.5 );
+    vec3 c = max(color.rgb, vec3(0));
+
+    // values of an sRGB working space are decoded, the target encodes them again
+    if (uniforms.linear == 0) {
+        c = srgb_to_linear(c);
+    }
+
+    c *= exp2(uniforms.exposure);
+
+    switch(uniforms.tonemap) {
+      case 1:
+        c = c / (1.0 + c); // reinhard
+        break;
+      case 2:
+        c = aces(c);
+        break;
+      default:
+        break;
+    }
+
+    c = pow(clamp(c, 0.0, 1.0), vec3(1.0 / max(uniforms.gamma, 0.01)));
+    f_color = vec4(c, clamp(color.a, 0.0, 1.0));
+}
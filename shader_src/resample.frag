@@ -0,0 +1,17 @@
+#version 450
+
+layout(location = 0) out vec4 f_color;
+layout(location = 1) in vec2 v_pos;
+
+// not used, the pipeline always binds a uniform block
+layout(set = 0, binding = 0) uniform Data {
+    float f0;
+} uniforms;
+
+layout(set =0, binding = 1) uniform texture2D tex1;
+layout(set =0, binding = 2) uniform sampler samp;
+
+
+void main() {
+    f_color = texture(sampler2D(tex1, samp), (v_pos+vec2(1,1))*0.5 );
+}
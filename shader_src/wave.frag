@@ -10,14 +10,14 @@ layout(location = 1) in vec2 v_pos;
 
 layout(set = 0, binding = 0) uniform Data {
     vec3 color;
-    float time;
+    float phase;
     float freq;
     float hard;
     float duty;
     float angle;
     float noise_amp;
     float noise_scale;
-    float noise_speed;
+    float noise_phase;
 
 } uniforms;
 
@@ -25,18 +25,17 @@ layout(set = 0, binding = 0) uniform Data {
 
 void main() {
     vec3 color = uniforms.color;
-    float time = uniforms.time;
     float freq = uniforms.freq;
     float hard = uniforms.hard;
     float duty = uniforms.duty;
     float angle = uniforms.angle;
     float noise_amp = uniforms.noise_amp;
     float noise_scale = uniforms.noise_scale;
-    float noise_speed = uniforms.noise_speed;
+    float noise_phase = uniforms.noise_phase;
 
-    float phase = (v_pos.x * sin(angle) + v_pos.y * cos(angle)) * freq * 20.14  + time;
+    float phase = (v_pos.x * sin(angle) + v_pos.y * cos(angle)) * freq * 20.14  + uniforms.phase;
 
-    float phase_nonise = cnoise(vec3(v_pos.x * noise_scale, v_pos.y * noise_scale, time * noise_speed));
+    float phase_nonise = cnoise(vec3(v_pos.x * noise_scale, v_pos.y * noise_scale, noise_phase));
     phase = phase + phase_nonise * noise_amp;
 
     float intensity =  (sin( phase ) + 1.0)/2.0;
@@ -0,0 +1,151 @@
+use crate::{ParameterEnd, ParameterEndpoint, ParameterFactory, ParameterStore, RenderContext};
+use nannou_osc::Message;
+use wgpu::{Device, Queue};
+
+///
+/// Show clock, the time all nodes use for animation
+///
+/// owned by the runtime and advanced once per frame with [Clock::tick],
+/// the nodes get its time using [Clock::context], program transitions and stream timecodes
+/// use [Clock::time] and [Clock::timecode].
+/// without a fixed step the clock follows the real time,
+/// with a fixed step every frame advances by the same time, e.g. to render files deterministically.
+///
+/// # OSC Parameters used
+///
+/// | Endpoint           | Description                                          |  Datatype  | Range      |
+/// |--------------------|------------------------------------------------------|------------|------------|
+/// | `/clock/pause`     | stop the time                                        | `bool`     |            |
+/// | `/clock/speed`     | time scale, 1 is real time                           | `f32`      | (0, 4.0)   |
+/// | `/clock/fixed_fps` | advance `1/fixed_fps` per frame, 0 uses the real time| `f32`      | (0, 240)   |
+/// | `/clock/bpm`       | tempo of the beat phase                              | `f32`      | (20, 300)  |
+///
+/// # OSC Commands
+///
+/// | Address       | Arguments    | Description                    |
+/// |---------------|--------------|--------------------------------|
+/// | `/clock/seek` | `time: f32`  | jump to a time in seconds      |
+pub struct Clock {
+    time: f32,
+    delta_time: f32,
+    /// beats since the start, the fraction is the beat phase
+    beats: f32,
+    /// number of ticks
+    ticks: u64,
+    seek: Option<f32>,
+    pause: ParameterEndpoint<bool>,
+    speed: ParameterEndpoint<f32>,
+    fixed_fps: ParameterEndpoint<f32>,
+    bpm: ParameterEndpoint<f32>,
+}
+
+impl Clock {
+    /// create a clock following the real time and register its parameters
+    pub fn new(store: &mut ParameterStore) -> Self {
+        Self::with_fixed_fps(store, 0.0)
+    }
+
+    /// create a clock advancing `1/fixed_fps` per frame, 0 follows the real time
+    pub fn with_fixed_fps(store: &mut ParameterStore, fixed_fps: f32) -> Self {
+        let mut factory = ParameterFactory::new("clock".to_string(), store);
+
+        Self {
+            time: 0.0,
+            delta_time: 0.0,
+            beats: 0.0,
+            ticks: 0,
+            seek: None,
+            pause: factory.build_default(false, "pause".to_string()),
+            speed: factory.build_default(1.0, "speed".to_string()),
+            fixed_fps: factory.build_default(fixed_fps, "fixed_fps".to_string()),
+            bpm: factory.build_default(120.0, "bpm".to_string()),
+        }
+    }
+
+    /// handle `/clock/seek`
+    pub fn update_osc(&mut self, msg: &Message) {
+        if msg.addr != "/clock/seek" {
+            return;
+        }
+
+        match msg
+            .args
+            .as_ref()
+            .and_then(|args| args.first())
+            .and_then(|arg| arg.clone().float())
+        {
+            Some(time) => self.seek(time),
+            None => println!("clock_error: /clock/seek needs a time in seconds"),
+        }
+    }
+
+    /// jump to `time` in seconds with the next [Clock::tick]
+    pub fn seek(&mut self, time: f32) {
+        self.seek = Some(time);
+    }
+
+    /// advance the clock by one frame
+    ///
+    /// `real_delta` is the real time since the last frame in seconds,
+    /// it is not used with a fixed step
+    pub fn tick(&mut self, store: &ParameterStore, real_delta: f32) {
+        let step = if self.pause.get(store) {
+            0.0
+        } else {
+            let fixed_fps = self.fixed_fps.get(store);
+            let frame_time = if fixed_fps > 0.0 {
+                1.0 / fixed_fps
+            } else {
+                real_delta
+            };
+            frame_time * self.speed.get(store)
+        };
+
+        let previous = self.time;
+        self.time = self.seek.take().unwrap_or(previous) + step;
+        self.delta_time = self.time - previous;
+        self.beats += self.delta_time * self.bpm.get(store) / 60.0;
+        self.ticks += 1;
+    }
+
+    /// show time in seconds
+    pub fn time(&self) -> f32 {
+        self.time
+    }
+
+    /// show time passed in the last tick, 0 while paused and negative after seeking backwards
+    pub fn delta_time(&self) -> f32 {
+        self.delta_time
+    }
+
+    /// timecode of the current frame sent with the NDI streams, in 1/10000 s
+    pub fn timecode(&self) -> i64 {
+        (self.time * 10000f32) as i64
+    }
+
+    /// index of the current frame, starting at 0 with the first tick
+    pub fn frame(&self) -> u64 {
+        self.ticks.saturating_sub(1)
+    }
+
+    /// position inside the current beat, from 0 to 1
+    pub fn beat_phase(&self) -> f32 {
+        self.beats.rem_euclid(1.0)
+    }
+
+    /// context of the current frame
+    pub fn context<'a>(
+        &self,
+        device: &'a Device,
+        queue: &'a Queue,
+        output_size: [u32; 2],
+    ) -> RenderContext<'a> {
+        RenderContext {
+            time: self.time,
+            delta_time: self.delta_time,
+            frame: self.frame(),
+            beat_phase: self.beat_phase(),
+            ..RenderContext::new(device, queue, output_size)
+        }
+    }
+}
@@ -1,11 +1,12 @@
-use wgpu::{Device, ShaderModuleDescriptorSpirV};
+use wgpu::{Device, ShaderModuleDescriptorSpirV, TextureFormat};
 
+use crate::{util::shader::read_shader_file, ParameterEndpoint, ParameterFactory, ParameterStore};
 use crate::{
-    util::shader::read_shader_file, ParameterEndpoint, ParameterFactory, ParameterStore, Vertex2D,
+    Fallback, InputPort, ParameterEnd, RenderContext, SamplerConfig, TextureNode,
+    UniformLayoutError,
 };
-use crate::{ParameterEnd, TextureNode};
 
-use super::shader_combiner::ShaderCombiner;
+use crate::shader_target::{Shader2DTarget, ShaderTargetBuilder};
 use crate::shapes::{FULL_SCREEN_QUAD, FULL_SCREEN_QUAD_INDEX};
 use nannou::image::EncodableLayout;
 
@@ -22,6 +23,17 @@ struct UniformsFade {
     fi12: f32,
     fi1i2: f32,
 }
+crate::uniform_layout!(UniformsFade {
+    f0,
+    f1,
+    f2,
+    f12,
+    fi1,
+    fi2,
+    f1i2,
+    fi12,
+    fi1i2
+});
 
 /// Combining 2 textures using different parameters
 ///
@@ -37,15 +49,22 @@ struct UniformsFade {
 /// | `./f2_inv`            | factor inverted second texture   | f32          | (0,1.0)  |
 /// | `./f1_mul_2_inv`      | combination of MUL and invert    | f32          | (0,1.0)  |
 /// | `./f1_inv_mul_2_inv`  | combination of MUL and invert    | f32          | (0,1.0)  |
-///     
+///
+/// # Inputs
+///
+/// | Port      | Description                      | Unconnected  |
+/// |-----------|----------------------------------|--------------|
+/// | `first`   | first texture                    | black        |
+/// | `second`  | second texture                   | black        |
+///
 /// # Target
-/// [ShaderCombiner] is used as render target/pipeline
+/// [ShaderTarget](crate::shader_target::ShaderTarget) is used as render target/pipeline
 ///
 /// ## shaders used
 /// - `shader/minimal2d.vert` shared simple vertex shader
 /// - `shader/fader.frag` shader for this
 pub struct FaderNode {
-    target: ShaderCombiner<UniformsFade, Vertex2D>,
+    target: Shader2DTarget<UniformsFade>,
     param: [ParameterEndpoint<f32>; 9],
 }
 
@@ -56,7 +75,8 @@ impl FaderNode {
         texture_size: [u32; 2],
         store: &mut ParameterStore,
         device: &Device,
-    ) -> Self {
+        format: TextureFormat,
+    ) -> Result<Self, UniformLayoutError> {
         let vert_raw = read_shader_file("shader/minimal2d_vert.spv");
         let frag_raw = read_shader_file("shader/fader_frag.spv");
 
@@ -98,26 +118,26 @@ impl FaderNode {
             factory.build_default(0.0, "f1_inv_mul_2_inv".to_string()),
         ];
 
-        let target = ShaderCombiner::new(
-            device,
-            texture_size,
+        let target = ShaderTargetBuilder::new(
             &vert,
             &frag,
-            2,
             &FULL_SCREEN_QUAD,
             &FULL_SCREEN_QUAD_INDEX,
             uniform,
-        );
+        )
+        .input_textures(2)
+        .format(format)
+        .build(device, texture_size)?;
 
-        Self { target, param }
+        Ok(Self { target, param })
     }
 }
 
 impl TextureNode for FaderNode {
     fn update(
         &mut self,
-        _app: &nannou::App,
-        window: &nannou::window::Window,
+        ctx: &RenderContext,
+        encoder: &mut nannou::wgpu::CommandEncoder,
         store: &ParameterStore,
         input: Vec<nannou::wgpu::TextureView>,
     ) {
@@ -145,23 +165,46 @@ impl TextureNode for FaderNode {
             fi1i2,
         };
 
-        let device = window.device();
+        let device = ctx.device;
 
-        self.target.begin(device);
-        self.target.set_uniforms(device, uniform);
-        self.target.render_pass(device, input);
-        self.target.end(window);
+        self.target.set_uniforms(ctx.queue, uniform);
+        self.target.render_pass(device, encoder, input);
     }
 
     fn output(&self) -> nannou::wgpu::TextureView {
         self.target.texture_view()
     }
 
+    fn input_ports(&self) -> Vec<InputPort> {
+        vec![
+            InputPort::optional("first", Fallback::Black),
+            InputPort::optional("second", Fallback::Black),
+        ]
+    }
+
+    fn set_samplers(&mut self, device: &Device, samplers: &[SamplerConfig]) {
+        self.target.set_samplers(device, samplers);
+    }
+
+    fn input_contributes(&self, store: &ParameterStore, slot: usize) -> bool {
+        // factors of all terms using the input
+        let factors: &[usize] = match slot {
+            0 => &[1, 3, 4, 6, 7, 8],
+            1 => &[2, 3, 5, 6, 7, 8],
+            _ => &[],
+        };
+        factors.iter().any(|&i| self.param[i].get(store) != 0.0)
+    }
+
+    fn time_dependent(&self, _store: &ParameterStore) -> bool {
+        false
+    }
+
     fn snapshot(
         &self,
-        window: &nannou::window::Window,
+        ctx: &RenderContext,
         texture_capturer: &nannou::wgpu::TextureCapturer,
     ) -> nannou::wgpu::TextueSnapshot {
-        self.target.snapshot(window, texture_capturer)
+        self.target.snapshot(ctx, texture_capturer)
     }
 }
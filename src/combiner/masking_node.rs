@@ -1,11 +1,12 @@
-use wgpu::{Device, ShaderModuleDescriptorSpirV};
+use wgpu::{Device, ShaderModuleDescriptorSpirV, TextureFormat};
 
+use crate::{util::shader::read_shader_file, ParameterEndpoint, ParameterFactory, ParameterStore};
 use crate::{
-    util::shader::read_shader_file, ParameterEndpoint, ParameterFactory, ParameterStore, Vertex2D,
+    Fallback, InputPort, ParameterEnd, RenderContext, SamplerConfig, TextureNode,
+    UniformLayoutError,
 };
-use crate::{ParameterEnd, TextureNode};
 
-use super::shader_combiner::ShaderCombiner;
+use crate::shader_target::{Shader2DTarget, ShaderTargetBuilder};
 use crate::shapes::{FULL_SCREEN_QUAD, FULL_SCREEN_QUAD_INDEX};
 use nannou::image::EncodableLayout;
 
@@ -14,12 +15,21 @@ use nannou::image::EncodableLayout;
 struct UniformsMasking {
     f0: f32,
 }
+crate::uniform_layout!(UniformsMasking { f0 });
 
 ///
 /// MaskingNode Combines 3 Textues
 /// combines 1st(a) and 2nd(b)  with 3rd(c) texture as Mask
 /// a*c + b*(1-c)
 ///
+/// # Inputs
+///
+/// | Port    | Description                      | Unconnected  |
+/// |---------|----------------------------------|--------------|
+/// | `a`     | texture where the mask is white  | required     |
+/// | `b`     | texture where the mask is black  | black        |
+/// | `mask`  | mask texture                     | white        |
+///
 /// # OSC Parameters used
 ///
 /// | Endpoint          | Description                      |  Datatype    | Range    |
@@ -27,13 +37,13 @@ struct UniformsMasking {
 /// | `./f0`            | bias of the mask                 | `f32`        | (0,1)    |
 ///
 /// /// # Target
-/// [ShaderCombiner] is used as render target/pipeline
+/// [ShaderTarget](crate::shader_target::ShaderTarget) is used as render target/pipeline
 ///
 /// ## shaders used
 /// - `shader/minimal2d.vert` shared simple vertex shader
 /// - `shader/masking.frag` shader for this
 pub struct MaskingNode {
-    target: ShaderCombiner<UniformsMasking, Vertex2D>,
+    target: Shader2DTarget<UniformsMasking>,
     param: [ParameterEndpoint<f32>; 1],
 }
 
@@ -43,7 +53,8 @@ impl MaskingNode {
         texture_size: [u32; 2],
         store: &mut ParameterStore,
         device: &Device,
-    ) -> Self {
+        format: TextureFormat,
+    ) -> Result<Self, UniformLayoutError> {
         let vert_raw = read_shader_file("shader/minimal2d_vert.spv");
         let frag_raw = read_shader_file("shader/masking_frag.spv");
 
@@ -65,52 +76,64 @@ impl MaskingNode {
         let mut factory = ParameterFactory::new(name, store);
         let param = [factory.build_default(0.0, "f0".to_string())];
 
-        let target = ShaderCombiner::new(
-            device,
-            texture_size,
+        let target = ShaderTargetBuilder::new(
             &vert,
             &frag,
-            3,
             &FULL_SCREEN_QUAD,
             &FULL_SCREEN_QUAD_INDEX,
             uniform,
-        );
+        )
+        .input_textures(3)
+        .format(format)
+        .build(device, texture_size)?;
 
-        Self { target, param }
+        Ok(Self { target, param })
     }
 }
 
 impl TextureNode for MaskingNode {
     fn update(
         &mut self,
-        _app: &nannou::App,
-        window: &nannou::window::Window,
+        ctx: &RenderContext,
+        encoder: &mut nannou::wgpu::CommandEncoder,
         store: &ParameterStore,
         input: Vec<nannou::wgpu::TextureView>,
     ) {
-        assert_eq!(input.len(), 3, "Not the correct number of Textures given");
-
         let f0 = self.param[0].get(store);
 
         let uniform = UniformsMasking { f0 };
 
-        let device = window.device();
+        let device = ctx.device;
 
-        self.target.begin(device);
-        self.target.set_uniforms(device, uniform);
-        self.target.render_pass(device, input);
-        self.target.end(window);
+        self.target.set_uniforms(ctx.queue, uniform);
+        self.target.render_pass(device, encoder, input);
     }
 
     fn output(&self) -> nannou::wgpu::TextureView {
         self.target.texture_view()
     }
 
+    fn input_ports(&self) -> Vec<InputPort> {
+        vec![
+            InputPort::required("a"),
+            InputPort::optional("b", Fallback::Black),
+            InputPort::optional("mask", Fallback::White),
+        ]
+    }
+
+    fn set_samplers(&mut self, device: &Device, samplers: &[SamplerConfig]) {
+        self.target.set_samplers(device, samplers);
+    }
+
+    fn time_dependent(&self, _store: &ParameterStore) -> bool {
+        false
+    }
+
     fn snapshot(
         &self,
-        window: &nannou::window::Window,
+        ctx: &RenderContext,
         texture_capturer: &nannou::wgpu::TextureCapturer,
     ) -> nannou::wgpu::TextueSnapshot {
-        self.target.snapshot(window, texture_capturer)
+        self.target.snapshot(ctx, texture_capturer)
     }
 }
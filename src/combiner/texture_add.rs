@@ -1,4 +1,4 @@
-use crate::{ModelUpdate};
+use crate::{ModelUpdate, RenderContext};
 use crate::{ParameterStore};
 use nannou::prelude::*;
 use nannou::wgpu::TextureView;
@@ -22,7 +22,7 @@ impl TextureAddModel {
 }
 
 impl ModelUpdate for TextureAddModel {
-    fn update_model(&mut self, _app: &App, _store: &ParameterStore ,input: Vec<TextureView>) -> Draw { 
+    fn update_model(&mut self, _ctx: &RenderContext, _store: &ParameterStore ,input: Vec<TextureView>) -> Draw { 
         let draw = Draw::new();
         let draw_blend = draw.color_blend(BlendComponent::OVER);
 
@@ -1,11 +1,11 @@
-use wgpu::{Device, ShaderModuleDescriptorSpirV};
+use wgpu::{Device, ShaderModuleDescriptorSpirV, TextureFormat};
 
+use crate::{util::shader::read_shader_file, ParameterEndpoint, ParameterFactory, ParameterStore};
 use crate::{
-    util::shader::read_shader_file, ParameterEndpoint, ParameterFactory, ParameterStore, Vertex2D,
+    InputPort, ParameterEnd, RenderContext, SamplerConfig, TextureNode, UniformLayoutError,
 };
-use crate::{ParameterEnd, TextureNode};
 
-use crate::combiner::shader_combiner::ShaderCombiner;
+use crate::shader_target::{Shader2DTarget, ShaderTargetBuilder};
 use crate::shapes::{FULL_SCREEN_QUAD, FULL_SCREEN_QUAD_INDEX};
 use nannou::image::EncodableLayout;
 
@@ -20,6 +20,15 @@ struct UniformsColorRamp {
     f2: f32,
     mode: i32,
 }
+crate::uniform_layout!(UniformsColorRamp {
+    c0,
+    f0,
+    c1,
+    f1,
+    c2,
+    f2,
+    mode
+});
 
 /// Color Ramp textures using different parameters
 ///
@@ -37,16 +46,22 @@ struct UniformsColorRamp {
 /// | `./f1`                | mid value setpoint               | `f32`        | (0,1.0)     |
 /// | `./f2`                | high value setpoint              | `f32`        | (0,1.0)     |
 /// | `./mode`              | interpolation mode               | `i32`        | (0,1,2,3,4) |
+///
+/// # Inputs
+///
+/// | Port      | Description                      | Unconnected  |
+/// |-----------|----------------------------------|--------------|
+/// | `input`   | texture to colorize              | required     |
 
 /// # Target
-/// [ShaderCombiner] is used as render target/pipeline
+/// [ShaderTarget](crate::shader_target::ShaderTarget) is used as render target/pipeline
 ///
 /// ## shaders used
 /// - `shader/minimal2d.vert` shared simple vertex shader
 /// - `shader/color_ramp.frag` shader for this
 ///
 pub struct ColorRampNode {
-    target: ShaderCombiner<UniformsColorRamp, Vertex2D>,
+    target: Shader2DTarget<UniformsColorRamp>,
     colors: [ParameterEndpoint<f32>; 3],
     param: [ParameterEndpoint<f32>; 3],
     mode: ParameterEndpoint<i32>,
@@ -58,7 +73,8 @@ impl ColorRampNode {
         texture_size: [u32; 2],
         store: &mut ParameterStore,
         device: &Device,
-    ) -> Self {
+        format: TextureFormat,
+    ) -> Result<Self, UniformLayoutError> {
         let vert_raw = read_shader_file("shader/minimal2d_vert.spv");
         let frag_raw = read_shader_file("shader/color_ramp_frag.spv");
 
@@ -101,31 +117,31 @@ impl ColorRampNode {
 
         let mode = factory.build_default(0, "mode".to_string());
 
-        let target = ShaderCombiner::new(
-            device,
-            texture_size,
+        let target = ShaderTargetBuilder::new(
             &vert,
             &frag,
-            1,
             &FULL_SCREEN_QUAD,
             &FULL_SCREEN_QUAD_INDEX,
             uniform,
-        );
+        )
+        .input_textures(1)
+        .format(format)
+        .build(device, texture_size)?;
 
-        Self {
+        Ok(Self {
             target,
             colors,
             param,
             mode,
-        }
+        })
     }
 }
 
 impl TextureNode for ColorRampNode {
     fn update(
         &mut self,
-        _app: &nannou::App,
-        window: &nannou::window::Window,
+        ctx: &RenderContext,
+        encoder: &mut nannou::wgpu::CommandEncoder,
         store: &ParameterStore,
         input: Vec<nannou::wgpu::TextureView>,
     ) {
@@ -150,23 +166,33 @@ impl TextureNode for ColorRampNode {
             mode,
         };
 
-        let device = window.device();
+        let device = ctx.device;
 
-        self.target.begin(device);
-        self.target.set_uniforms(device, uniform);
-        self.target.render_pass(device, input);
-        self.target.end(window);
+        self.target.set_uniforms(ctx.queue, uniform);
+        self.target.render_pass(device, encoder, input);
     }
 
     fn output(&self) -> nannou::wgpu::TextureView {
         self.target.texture_view()
     }
 
+    fn input_ports(&self) -> Vec<InputPort> {
+        vec![InputPort::required("input")]
+    }
+
+    fn set_samplers(&mut self, device: &Device, samplers: &[SamplerConfig]) {
+        self.target.set_samplers(device, samplers);
+    }
+
+    fn time_dependent(&self, _store: &ParameterStore) -> bool {
+        false
+    }
+
     fn snapshot(
         &self,
-        window: &nannou::window::Window,
+        ctx: &RenderContext,
         texture_capturer: &nannou::wgpu::TextureCapturer,
     ) -> nannou::wgpu::TextueSnapshot {
-        self.target.snapshot(window, texture_capturer)
+        self.target.snapshot(ctx, texture_capturer)
     }
 }
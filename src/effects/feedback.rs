@@ -0,0 +1,180 @@
+use wgpu::{Device, ShaderModuleDescriptorSpirV, TextureFormat};
+
+use crate::{util::shader::read_shader_file, ParameterEndpoint, ParameterFactory, ParameterStore};
+use crate::{
+    Fallback, InputPort, ParameterEnd, RenderContext, SamplerAddress, SamplerConfig, TextureNode,
+    UniformLayoutError,
+};
+
+use crate::shader_target::{Shader2DTarget, ShaderTargetBuilder};
+use crate::shapes::{FULL_SCREEN_QUAD, FULL_SCREEN_QUAD_INDEX};
+use nannou::image::EncodableLayout;
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct UniformsFeedback {
+    decay: f32,
+    zoom: f32,
+    rotate: f32,
+}
+crate::uniform_layout!(UniformsFeedback {
+    decay,
+    zoom,
+    rotate
+});
+
+/// Previous frame of another node, for trails, echoes and video feedback
+///
+/// The source is set with the `feedback` field of the node inside the graph file
+/// or `/graph/feedback`, it can be any node, also one that uses this node as input.
+/// After all nodes are updated the output of the source is zoomed, rotated and darkened
+/// into the back texture of a ping-pong pair, that is used as output in the next frame.
+///
+/// | Endpoint              | Description                      |  Datatype    | Range       |
+/// |-----------------------|----------------------------------|--------------|-------------|
+/// | `./decay`             | brightness of the previous frame | `f32`        | (0, 1.0)    |
+/// | `./zoom`              | zoom per frame, 1 keeps the size | `f32`        | (0.5, 2.0)  |
+/// | `./rotate`            | rotation per frame in radians    | `f32`        | (-PI, PI)   |
+///
+/// # Inputs
+///
+/// the node has no input ports, the source is not an edge of the graph.
+/// its sampling is set on the `feedback` port, e.g. `"samplers": { "feedback": { "address": "mirror" } }`.
+/// by default the port uses the `border` address, a zoom out fades to black at the edges
+///
+/// # Target
+/// two [ShaderTarget](crate::shader_target::ShaderTarget)s are used as ping-pong render targets
+///
+/// ## shaders used
+/// - `shader/minimal2d.vert` shared simple vertex shader
+/// - `shader/feedback.frag` shader for this
+///
+pub struct FeedbackNode {
+    targets: [Shader2DTarget<UniformsFeedback>; 2],
+    /// index of the target used as output
+    current: usize,
+    decay: ParameterEndpoint<f32>,
+    zoom: ParameterEndpoint<f32>,
+    rotate: ParameterEndpoint<f32>,
+}
+
+impl FeedbackNode {
+    pub fn new(
+        name: String,
+        texture_size: [u32; 2],
+        store: &mut ParameterStore,
+        device: &Device,
+        format: TextureFormat,
+    ) -> Result<Self, UniformLayoutError> {
+        let vert_raw = read_shader_file("shader/minimal2d_vert.spv");
+        let frag_raw = read_shader_file("shader/feedback_frag.spv");
+
+        let uniform = UniformsFeedback {
+            decay: 0.95,
+            zoom: 1.0,
+            rotate: 0.0,
+        };
+
+        let target = || {
+            let vert = ShaderModuleDescriptorSpirV {
+                label: Some("minimal2d_vert"),
+                source: nannou::wgpu::util::make_spirv_raw(vert_raw.as_bytes()),
+            };
+            let frag = ShaderModuleDescriptorSpirV {
+                label: Some("feedback_frag"),
+                source: nannou::wgpu::util::make_spirv_raw(frag_raw.as_bytes()),
+            };
+            ShaderTargetBuilder::new(
+                &vert,
+                &frag,
+                &FULL_SCREEN_QUAD,
+                &FULL_SCREEN_QUAD_INDEX,
+                uniform,
+            )
+            .input_textures(1)
+            .format(format)
+            .build(device, texture_size)
+        };
+        let mut targets = [target()?, target()?];
+        for target in targets.iter_mut() {
+            target.set_samplers(device, &[border_sampler()]);
+        }
+
+        let mut factory = ParameterFactory::new(name, store);
+        let decay = factory.build_default(uniform.decay, "decay".to_string());
+        let zoom = factory.build_default(uniform.zoom, "zoom".to_string());
+        let rotate = factory.build_default(uniform.rotate, "rotate".to_string());
+
+        Ok(Self {
+            targets,
+            current: 0,
+            decay,
+            zoom,
+            rotate,
+        })
+    }
+}
+
+/// default sampling of the `feedback` port, outside of the previous frame is black
+fn border_sampler() -> SamplerConfig {
+    SamplerConfig {
+        address: SamplerAddress::Border,
+        ..SamplerConfig::default()
+    }
+}
+
+impl TextureNode for FeedbackNode {
+    fn update(
+        &mut self,
+        _ctx: &RenderContext,
+        _encoder: &mut nannou::wgpu::CommandEncoder,
+        _store: &ParameterStore,
+        _input: Vec<nannou::wgpu::TextureView>,
+    ) {
+        // the output is rendered in update_feedback, after the source was updated
+    }
+
+    fn update_feedback(
+        &mut self,
+        ctx: &RenderContext,
+        encoder: &mut nannou::wgpu::CommandEncoder,
+        store: &ParameterStore,
+        source: nannou::wgpu::TextureView,
+    ) {
+        let uniform = UniformsFeedback {
+            decay: self.decay.get(store),
+            zoom: self.zoom.get(store),
+            rotate: self.rotate.get(store),
+        };
+
+        let device = ctx.device;
+        let back = &mut self.targets[1 - self.current];
+
+        back.set_uniforms(ctx.queue, uniform);
+        back.render_pass(device, encoder, vec![source]);
+
+        self.current = 1 - self.current;
+    }
+
+    fn feedback_port(&self) -> Option<InputPort> {
+        Some(InputPort::optional("feedback", Fallback::Black).sampler(border_sampler()))
+    }
+
+    fn set_samplers(&mut self, device: &Device, samplers: &[SamplerConfig]) {
+        for target in self.targets.iter_mut() {
+            target.set_samplers(device, samplers);
+        }
+    }
+
+    fn output(&self) -> nannou::wgpu::TextureView {
+        self.targets[self.current].texture_view()
+    }
+
+    fn snapshot(
+        &self,
+        ctx: &RenderContext,
+        texture_capturer: &nannou::wgpu::TextureCapturer,
+    ) -> nannou::wgpu::TextueSnapshot {
+        self.targets[self.current].snapshot(ctx, texture_capturer)
+    }
+}
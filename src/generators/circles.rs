@@ -4,7 +4,7 @@ use rand::distributions::Uniform;
 use rand::prelude::*;
 
 use crate::{ModelUpdate, ParameterEnd};
-use crate::{ParameterEndpoint, ParameterFactory, ParameterStore};
+use crate::{ParameterEndpoint, ParameterFactory, ParameterStore, PhaseAccumulator, RenderContext};
 use nannou::prelude::*;
 
 ///
@@ -22,7 +22,7 @@ use nannou::prelude::*;
 /// |`./res`            | resolution of the circles        |`f32`         | (3, 255) |
 /// |`./speed`          | speed of the animation           |`f32`         | (0, 5)   |
 /// |`./distance`       | max radius of the grow animation |`f32`         | (0, 10)  |
-/// |`./rotation_speed` | speed of the rotation animation  |`f32`         | (0, 2)   |
+/// |`./rotation_speed` | rotation in radians per second   |`f32`         | (0, 2)   |
 /// |
 ///
 
@@ -30,7 +30,9 @@ pub struct CircleGenerator {
     texture_size: [u32; 2],
     parameters: Vec<ParameterEndpoint<f32>>,
     color: ParameterEndpoint<f32>,
-    last_time: f32,
+    /// position of the grow animation
+    time: PhaseAccumulator,
+    rotation: PhaseAccumulator,
 }
 
 impl CircleGenerator {
@@ -48,15 +50,17 @@ impl CircleGenerator {
         parameters.push(factory.build_default(2.0, "width".to_string()));
         parameters.push(factory.build_default(50.0, "count".to_string()));
         parameters.push(factory.build_default(255.0, "res".to_string()));
-        parameters.push(factory.build_default(1.0, "speed".to_string()));
+        let time = PhaseAccumulator::new(factory.build_default(1.0, "speed".to_string()));
         parameters.push(factory.build_default(1.0, "distance".to_string()));
-        parameters.push(factory.build_default(1.0, "rotation_speed".to_string()));
+        let rotation =
+            PhaseAccumulator::new(factory.build_default(1.0, "rotation_speed".to_string()));
 
         Self {
             texture_size,
             parameters,
             color,
-            last_time: 0.0f32,
+            time,
+            rotation,
         }
     }
 }
@@ -64,7 +68,7 @@ impl CircleGenerator {
 impl ModelUpdate for CircleGenerator {
     fn update_model(
         &mut self,
-        app: &nannou::App,
+        ctx: &RenderContext,
         store: &ParameterStore,
         _input: Vec<nannou::wgpu::TextureView>,
     ) -> Draw {
@@ -79,12 +83,11 @@ impl ModelUpdate for CircleGenerator {
         let width: f32 = handles.next().unwrap().into();
         let count: f32 = handles.next().unwrap().into();
         let res: f32 = handles.next().unwrap().into();
-        let speed: f32 = handles.next().unwrap().into();
         let distance: f32 = handles.next().unwrap().into();
-        let rot_speed: f32 = handles.next().unwrap().into();
 
-        // get the current runtime for animation
-        let time: f32 = self.last_time + speed * app.fps();
+        // move the animation with the current speeds
+        let time = self.time.advance(store, ctx.delta_time);
+        let rotation = self.rotation.advance(store, ctx.delta_time);
 
         let draw = Draw::new();
 
@@ -92,7 +95,7 @@ impl ModelUpdate for CircleGenerator {
         let scale = (self.texture_size[0] as f32 + self.texture_size[1] as f32) / 2.0;
         let blend = draw.color_blend(BLEND_ADD);
         for i in 0..(count as i32 + 2) {
-            let time_i = time * speed * scale * 0.1f32;
+            let time_i = time * scale * 0.1f32;
             let raw = time_i + (i as f32) * distance * 10.0f32;
             let r = raw.rem(scale);
             let seed = (raw / scale).ceil() as u64;
@@ -103,8 +106,7 @@ impl ModelUpdate for CircleGenerator {
             let dist = Uniform::new(-scale, scale);
             let x = rng.sample(dist);
             let y = rng.sample(dist);
-            let radians =
-                rng.sample(Uniform::new(0.0, std::f32::consts::PI * 2.0)) + time_i * rot_speed;
+            let radians = rng.sample(Uniform::new(0.0, std::f32::consts::PI * 2.0)) + rotation;
             blend
                 .ellipse()
                 .no_fill()
@@ -117,8 +119,6 @@ impl ModelUpdate for CircleGenerator {
                 .z(f);
         }
 
-        self.last_time = time;
-
         draw
     }
 }
@@ -2,13 +2,15 @@
 ///
 /// EXPERIMENTAL
 use crate::ParameterStore;
+use crate::RenderContext;
 use crate::TextureNode;
+use crate::UniformLayoutError;
 
-use crate::shader_target::Shader2DTarget;
+use crate::shader_target::{Shader2DTarget, ShaderTargetBuilder};
 use crate::shapes::{FULL_SCREEN_QUAD, FULL_SCREEN_QUAD_INDEX};
 use crate::util::shader::read_shader_file;
 use nannou::image::EncodableLayout;
-use nannou::wgpu::{Device, TextueSnapshot, TextureCapturer};
+use nannou::wgpu::{Device, TextueSnapshot, TextureCapturer, TextureFormat};
 use wgpu::ShaderModuleDescriptorSpirV;
 
 #[repr(C)]
@@ -16,6 +18,7 @@ use wgpu::ShaderModuleDescriptorSpirV;
 struct UniformsCloud {
     time: f32,
 }
+crate::uniform_layout!(UniformsCloud { time });
 
 pub struct CloudsNode {
     target: Shader2DTarget<UniformsCloud>,
@@ -28,7 +31,8 @@ impl CloudsNode {
         texture_size: [u32; 2],
         _store: &mut ParameterStore,
         device: &Device,
-    ) -> Self {
+        format: TextureFormat,
+    ) -> Result<Self, UniformLayoutError> {
         let vert_raw = read_shader_file("shader/minimal2d_vert.spv");
         let frag_raw = read_shader_file("shader/clouds_frag.spv");
 
@@ -47,51 +51,45 @@ impl CloudsNode {
 
         let uniform = UniformsCloud { time: 0.0 };
 
-        let target = Shader2DTarget::new(
-            device,
-            texture_size,
+        let target = ShaderTargetBuilder::new(
             &vert,
             &frag,
             &FULL_SCREEN_QUAD,
             &FULL_SCREEN_QUAD_INDEX,
             uniform,
-        );
-        Self {
+        )
+        .format(format)
+        .build(device, texture_size)?;
+        Ok(Self {
             target,
             //param,
-        }
+        })
     }
 }
 
 impl TextureNode for CloudsNode {
     fn update(
         &mut self,
-        app: &nannou::App,
-        window: &nannou::window::Window,
+        ctx: &RenderContext,
+        encoder: &mut nannou::wgpu::CommandEncoder,
         _store: &ParameterStore,
         _input: Vec<nannou::wgpu::TextureView>,
     ) {
-        let time = app.time;
+        let time = ctx.time;
 
         let uniform = UniformsCloud { time };
 
-        let device = window.device();
+        let device = ctx.device;
 
-        self.target.begin(device);
-        self.target.set_uniforms(device, uniform);
-        self.target.render_pass();
-        self.target.end(window);
+        self.target.set_uniforms(ctx.queue, uniform);
+        self.target.render_pass(device, encoder, Vec::new());
     }
 
     fn output(&self) -> nannou::wgpu::TextureView {
         self.target.texture_view()
     }
 
-    fn snapshot(
-        &self,
-        window: &nannou::window::Window,
-        texture_capturer: &TextureCapturer,
-    ) -> TextueSnapshot {
-        self.target.snapshot(window, texture_capturer)
+    fn snapshot(&self, ctx: &RenderContext, texture_capturer: &TextureCapturer) -> TextueSnapshot {
+        self.target.snapshot(ctx, texture_capturer)
     }
 }
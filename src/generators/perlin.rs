@@ -1,13 +1,16 @@
-use crate::shader_target::Shader2DTarget;
+use crate::shader_target::{Shader2DTarget, ShaderTargetBuilder};
 use crate::shapes::{FULL_SCREEN_QUAD, FULL_SCREEN_QUAD_INDEX};
 use crate::util::shader::read_shader_file;
 use crate::ParameterEnd;
 use crate::ParameterEndpoint;
 use crate::ParameterFactory;
 use crate::ParameterStore;
+use crate::PhaseAccumulator;
+use crate::RenderContext;
 use crate::TextureNode;
+use crate::UniformLayoutError;
 use nannou::image::EncodableLayout;
-use nannou::wgpu::{Device, TextueSnapshot, TextureCapturer};
+use nannou::wgpu::{Device, TextueSnapshot, TextureCapturer, TextureFormat};
 use wgpu::ShaderModuleDescriptorSpirV;
 
 /// Uniform data passed on to render Wave Texture for [WaveTextureNode]
@@ -22,6 +25,15 @@ struct UniformsPerlin {
     sy: f32,
     octave: i32,
 }
+crate::uniform_layout!(UniformsPerlin {
+    color,
+    tx,
+    ty,
+    tz,
+    sx,
+    sy,
+    octave
+});
 
 /// generating a wavelike texture
 ///
@@ -40,7 +52,9 @@ struct UniformsPerlin {
 pub struct PerlinTextureNode {
     target: Shader2DTarget<UniformsPerlin>,
     color: ParameterEndpoint<f32>,
-    param: [ParameterEndpoint<f32>; 6],
+    /// offsets moved by `speedx`, `speedy` and `speedz`
+    offset: [PhaseAccumulator; 3],
+    param: [ParameterEndpoint<f32>; 3],
 }
 
 impl PerlinTextureNode {
@@ -49,7 +63,8 @@ impl PerlinTextureNode {
         texture_size: [u32; 2],
         store: &mut ParameterStore,
         device: &Device,
-    ) -> Self {
+        format: TextureFormat,
+    ) -> Result<Self, UniformLayoutError> {
         let vert_raw = read_shader_file("shader/minimal2d_vert.spv");
         let frag_raw = read_shader_file("shader/perlin_frag.spv");
 
@@ -78,49 +93,52 @@ impl PerlinTextureNode {
 
         let mut factory = ParameterFactory::new(name, store);
         let color = factory.build_array_default(1.0, 3, "color".to_string());
+        let offset = [
+            PhaseAccumulator::new(factory.build_default(0.0, "speedx".to_string())),
+            PhaseAccumulator::new(factory.build_default(0.0, "speedy".to_string())),
+            PhaseAccumulator::new(factory.build_default(0.1, "speedz".to_string())),
+        ];
         let param = [
-            factory.build_default(0.0, "speedx".to_string()),
-            factory.build_default(0.0, "speedy".to_string()),
-            factory.build_default(0.1, "speedz".to_string()),
             factory.build_default(0.8, "scalex".to_string()),
             factory.build_default(0.8, "scaley".to_string()),
             factory.build_default(4.0, "octave".to_string()),
         ];
 
-        let target = Shader2DTarget::new(
-            device,
-            texture_size,
+        let target = ShaderTargetBuilder::new(
             &vert,
             &frag,
             &FULL_SCREEN_QUAD,
             &FULL_SCREEN_QUAD_INDEX,
             uniform,
-        );
-        Self {
+        )
+        .format(format)
+        .build(device, texture_size)?;
+        Ok(Self {
             target,
             color,
+            offset,
             param,
-        }
+        })
     }
 }
 
 impl TextureNode for PerlinTextureNode {
     fn update(
         &mut self,
-        app: &nannou::App,
-        window: &nannou::window::Window,
+        ctx: &RenderContext,
+        encoder: &mut nannou::wgpu::CommandEncoder,
         store: &ParameterStore,
         _input: Vec<nannou::wgpu::TextureView>,
     ) {
         // get parameter from osc parameter store
         let color_vec = self.color.get_vec(store);
         let color = [color_vec[0], color_vec[1], color_vec[2]];
-        let tx = app.time * self.param[0].get(store);
-        let ty = app.time * self.param[1].get(store);
-        let tz = app.time * self.param[2].get(store);
-        let sx = self.param[3].get(store);
-        let sy = self.param[4].get(store);
-        let octave: i32 = self.param[5].get(store) as i32;
+        let tx = self.offset[0].advance(store, ctx.delta_time);
+        let ty = self.offset[1].advance(store, ctx.delta_time);
+        let tz = self.offset[2].advance(store, ctx.delta_time);
+        let sx = self.param[0].get(store);
+        let sy = self.param[1].get(store);
+        let octave: i32 = self.param[2].get(store) as i32;
 
         // build uniform for the shader
         let uniform = UniformsPerlin {
@@ -133,24 +151,23 @@ impl TextureNode for PerlinTextureNode {
             octave,
         };
 
-        let device = window.device();
+        let device = ctx.device;
 
         // render
-        self.target.begin(device);
-        self.target.set_uniforms(device, uniform);
-        self.target.render_pass();
-        self.target.end(window);
+        self.target.set_uniforms(ctx.queue, uniform);
+        self.target.render_pass(device, encoder, Vec::new());
     }
 
     fn output(&self) -> nannou::wgpu::TextureView {
         self.target.texture_view()
     }
 
-    fn snapshot(
-        &self,
-        window: &nannou::window::Window,
-        texture_capturer: &TextureCapturer,
-    ) -> TextueSnapshot {
-        self.target.snapshot(window, texture_capturer)
+    fn snapshot(&self, ctx: &RenderContext, texture_capturer: &TextureCapturer) -> TextueSnapshot {
+        self.target.snapshot(ctx, texture_capturer)
+    }
+
+    /// the noise only moves if a speed is set
+    fn time_dependent(&self, store: &ParameterStore) -> bool {
+        self.offset.iter().any(|p| p.speed(store) != 0.0)
     }
 }
@@ -1,5 +1,5 @@
 use crate::{ModelUpdate, ParameterEnd};
-use crate::{ParameterEndpoint, ParameterFactory, ParameterStore};
+use crate::{ParameterEndpoint, ParameterFactory, ParameterStore, PhaseAccumulator, RenderContext};
 use nannou::prelude::*;
 use std::ops::Rem;
 
@@ -15,14 +15,14 @@ use std::ops::Rem;
 /// |`./width`          | width of a single stripe         |`f32`         | (1, ...) |         
 /// |`./count`          | count of stipes                  |`f32`         | (1, ...) |
 /// |`./angle           | angle of the stripes             |`f32`         | (0, pi/2)|
-/// |`./speed`          | speed of the animation           |`f32`         | (0, ...) |
+/// |`./speed`          | stripes moved per second         |`f32`         | (0, ...) |
 /// |
 ///
 pub struct StripeGenerator {
     texture_size: [u32; 2],
     parameters: Vec<ParameterEndpoint<f32>>,
     color: ParameterEndpoint<f32>,
-    last_time: f32,
+    offset: PhaseAccumulator,
 }
 
 impl StripeGenerator {
@@ -40,13 +40,13 @@ impl StripeGenerator {
         parameters.push(factory.build_default(0.3, "width".to_string()));
         parameters.push(factory.build_default(10.0, "count".to_string()));
         parameters.push(factory.build_default(0.5, "angle".to_string()));
-        parameters.push(factory.build_default(1.0, "speed".to_string()));
+        let offset = PhaseAccumulator::new(factory.build_default(1.0, "speed".to_string()));
 
         Self {
             texture_size,
             parameters,
             color,
-            last_time: 0.0f32,
+            offset,
         }
     }
 }
@@ -54,7 +54,7 @@ impl StripeGenerator {
 impl ModelUpdate for StripeGenerator {
     fn update_model(
         &mut self,
-        app: &nannou::App,
+        ctx: &RenderContext,
         store: &ParameterStore,
         _input: Vec<nannou::wgpu::TextureView>,
     ) -> Draw {
@@ -68,8 +68,7 @@ impl ModelUpdate for StripeGenerator {
         let width: f32 = handles.next().unwrap().into();
         let count: f32 = handles.next().unwrap().into();
         let angle: f32 = handles.next().unwrap().into();
-        let speed: f32 = handles.next().unwrap().into();
-        let time: f32 = self.last_time + speed * app.fps();
+        let offset = self.offset.advance(store, ctx.delta_time);
 
         let height = self.texture_size[1] as f32 * 2.5;
 
@@ -84,7 +83,7 @@ impl ModelUpdate for StripeGenerator {
         draw.background().color(BLACK);
 
         for i in 0..(count as i32 + 2) {
-            let x = ((time * 10.0) + (tile * (i as f32))).rem(tex_width) + x_start;
+            let x = (tile * (offset + i as f32)).rem(tex_width) + x_start;
 
             draw.quad()
                 .x_y(x, 0.0)
@@ -93,8 +92,6 @@ impl ModelUpdate for StripeGenerator {
                 .color(rgb);
         }
 
-        self.last_time = time;
-
         draw
     }
 }
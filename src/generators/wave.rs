@@ -1,13 +1,16 @@
-use crate::shader_target::Shader2DTarget;
+use crate::shader_target::{Shader2DTarget, ShaderTargetBuilder};
 use crate::shapes::{FULL_SCREEN_QUAD, FULL_SCREEN_QUAD_INDEX};
 use crate::util::shader::read_shader_file;
 use crate::ParameterEnd;
 use crate::ParameterEndpoint;
 use crate::ParameterFactory;
 use crate::ParameterStore;
+use crate::PhaseAccumulator;
+use crate::RenderContext;
 use crate::TextureNode;
+use crate::UniformLayoutError;
 use nannou::image::EncodableLayout;
-use nannou::wgpu::{Device, TextueSnapshot, TextureCapturer};
+use nannou::wgpu::{Device, TextueSnapshot, TextureCapturer, TextureFormat};
 use wgpu::ShaderModuleDescriptorSpirV;
 
 /// Uniform data passed on to render Wave Texture for [WaveTextureNode]
@@ -15,15 +18,26 @@ use wgpu::ShaderModuleDescriptorSpirV;
 #[derive(Clone, Copy)]
 struct UniformsWave {
     color: [f32; 3],
-    time: f32,
+    phase: f32,
     freq: f32,
     hard: f32,
     duty: f32,
     angle: f32,
     noise_amp: f32,
     noise_scale: f32,
-    noise_speed: f32,
+    noise_phase: f32,
 }
+crate::uniform_layout!(UniformsWave {
+    color,
+    phase,
+    freq,
+    hard,
+    duty,
+    angle,
+    noise_amp,
+    noise_scale,
+    noise_phase
+});
 
 /// generating a wavelike texture
 ///
@@ -32,6 +46,7 @@ struct UniformsWave {
 /// | Endpoint          | Description                        |  Datatype    | Range        |
 /// |-------------------|------------------------------------|--------------|--------------|
 /// |`./color`          | color of the waves                 |`[3, f32]`    | (0, 1.0)     |
+/// |`./speed`          | speed of the waves                 |`f32`         | (..., ...)   |
 /// |`./freq`           | how many waves                     |`f32`         | (1, ...)     |         
 /// |`./hard `          | 'hardness'/ slope                  |`f32`         | (0, ...) LOG |
 /// |`./duty            | duty cycle / thickness             |`f32`         | (0, ...)     |
@@ -44,7 +59,9 @@ struct UniformsWave {
 pub struct WaveTextureNode {
     target: Shader2DTarget<UniformsWave>,
     color: ParameterEndpoint<f32>,
-    param: [ParameterEndpoint<f32>; 7],
+    param: [ParameterEndpoint<f32>; 6],
+    phase: PhaseAccumulator,
+    noise_phase: PhaseAccumulator,
 }
 
 impl WaveTextureNode {
@@ -53,7 +70,8 @@ impl WaveTextureNode {
         texture_size: [u32; 2],
         store: &mut ParameterStore,
         device: &Device,
-    ) -> Self {
+        format: TextureFormat,
+    ) -> Result<Self, UniformLayoutError> {
         let vert_raw = read_shader_file("shader/minimal2d_vert.spv");
         let frag_raw = read_shader_file("shader/wave_frag.spv");
 
@@ -72,18 +90,19 @@ impl WaveTextureNode {
 
         let uniform = UniformsWave {
             color: [1.0, 1.0, 1.0],
-            time: 0.0,
+            phase: 0.0,
             freq: 1.0,
             hard: 1.0,
             duty: 0.5,
             angle: 0.5,
             noise_amp: 0.0,
             noise_scale: 0.0,
-            noise_speed: 1.0,
+            noise_phase: 0.0,
         };
 
         let mut factory = ParameterFactory::new(name, store);
         let color = factory.build_array_default(1.0, 3, "color".to_string());
+        let phase = PhaseAccumulator::new(factory.build_default(1.0, "speed".to_string()));
         let param = [
             factory.build_default(3.0, "freq".to_string()),
             factory.build_default(0.0, "hard".to_string()),
@@ -91,77 +110,74 @@ impl WaveTextureNode {
             factory.build_default(1.0, "angle".to_string()),
             factory.build_default(30.0, "noise_amp".to_string()),
             factory.build_default(1.5, "noise_scale".to_string()),
-            factory.build_default(0.15, "noise_speed".to_string()),
         ];
+        let noise_phase =
+            PhaseAccumulator::new(factory.build_default(0.15, "noise_speed".to_string()));
 
-        let target = Shader2DTarget::new(
-            device,
-            texture_size,
+        let target = ShaderTargetBuilder::new(
             &vert,
             &frag,
             &FULL_SCREEN_QUAD,
             &FULL_SCREEN_QUAD_INDEX,
             uniform,
-        );
-        Self {
+        )
+        .format(format)
+        .build(device, texture_size)?;
+        Ok(Self {
             target,
             color,
             param,
-        }
+            phase,
+            noise_phase,
+        })
     }
 }
 
 impl TextureNode for WaveTextureNode {
     fn update(
         &mut self,
-        app: &nannou::App,
-        window: &nannou::window::Window,
+        ctx: &RenderContext,
+        encoder: &mut nannou::wgpu::CommandEncoder,
         store: &ParameterStore,
         _input: Vec<nannou::wgpu::TextureView>,
     ) {
         // get parameter from osc parameter store
         let color_vec = self.color.get_vec(store);
         let color = [color_vec[0], color_vec[1], color_vec[2]];
-        let time = app.time;
+        let phase = self.phase.advance(store, ctx.delta_time);
         let freq = self.param[0].get(store);
         let hard = self.param[1].get(store);
         let duty = self.param[2].get(store);
         let angle = self.param[3].get(store);
         let noise_amp = self.param[4].get(store);
         let noise_scale = self.param[5].get(store);
-        let noise_speed = self.param[6].get(store);
+        let noise_phase = self.noise_phase.advance(store, ctx.delta_time);
 
         // build uniform for the shader
         let uniform = UniformsWave {
             color,
-            time,
+            phase,
             freq,
             hard,
             duty,
             angle,
             noise_amp,
             noise_scale,
-            noise_speed,
+            noise_phase,
         };
 
-        let device = window.device();
+        let device = ctx.device;
 
         // render
-        self.target.begin(device);
-        self.target.set_uniforms(device, uniform);
-        self.target.render_pass();
-        self.target.end(window);
+        self.target.set_uniforms(ctx.queue, uniform);
+        self.target.render_pass(device, encoder, Vec::new());
     }
 
     fn output(&self) -> nannou::wgpu::TextureView {
         self.target.texture_view()
     }
 
-    fn snapshot(
-        &self,
-        window: &nannou::window::Window,
-        texture_capturer: &TextureCapturer,
-    ) -> TextueSnapshot {
-        self.target.snapshot(window, texture_capturer)
+    fn snapshot(&self, ctx: &RenderContext, texture_capturer: &TextureCapturer) -> TextueSnapshot {
+        self.target.snapshot(ctx, texture_capturer)
     }
 }
@@ -0,0 +1,606 @@
+///
+/// # Graph Description
+/// Declarative description of a [TextureTree], that can be loaded from and stored to a JSON file.
+/// A node can be used as input of several nodes.
+///
+/// ```json
+/// {
+///   "texture_size": [512, 512],
+///   "format": "rgba16float",
+///   "root": "fader",
+///   "nodes": [
+///     { "name": "wave", "type": "wave", "format": "rgba8" },
+///     { "name": "stripes", "type": "stripes", "size": [256, 256] },
+///     { "name": "clouds", "type": "clouds", "scale": 0.25 },
+///     { "name": "trails", "type": "feedback", "feedback": "fader" },
+///     { "name": "fader", "type": "fader", "inputs": ["wave", "trails"],
+///       "samplers": { "second": { "address": "mirror", "mipmaps": true } } }
+///   ],
+///   "stream": { "name": "visgen_graph", "framerate": 60 },
+///   "outputs": [
+///     { "name": "projector_2", "node": "stripes", "framerate": 30, "resolution": [1920, 1080] }
+///   ]
+/// }
+/// ```
+///
+/// `root` is the main output, it is sent as the NDI stream described by `stream`.
+/// `outputs` are sent as additional NDI streams.
+/// `groups` define reusable subgraphs, see [GroupDescription].
+/// `feedback` passes the previous frame of a node to another node, it is not an input,
+/// so it can point downstream without creating a cycle.
+///
+/// Every node renders at its own size, inputs of a different size or aspect are
+/// stretched to the size of the node using linear filtering.
+///
+/// `samplers` changes how a node samples the inputs of a port, by port name, see [SamplerConfig].
+///
+/// `format` selects the [NodeFormat] of all nodes, a node can override it.
+/// Float formats avoid clipping inside the graph, the output is converted to
+/// [crate::OUTPUT_FORMAT] only when it is shown or sent, see [crate::OutputStage].
+///
+use crate::{
+    BuildContext, NodeFormat, NodeInfo, NodeOptions, NodeRegistry, ParameterStore, SamplerConfig,
+    TextureNode, TextureTree, UniformLayoutError,
+};
+use indextree::{Arena, NodeId};
+use nannou::wgpu::{Device, Features};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::error::Error;
+use std::fmt::Display;
+use std::fs::File;
+use std::path::Path;
+
+#[derive(Debug)]
+pub enum GraphError {
+    IoError(std::io::Error),
+    JsonError(serde_json::Error),
+    UnknownNodeType(String),
+    UnknownNode(String),
+    DuplicateNode(String),
+    Cycle(String),
+    InvalidOption(String, String, serde_json::Error),
+    InvalidSlot(String, usize),
+    RootNode(String),
+    OutputNode(String),
+    DuplicateOutput(String),
+    InvalidSize(String),
+    MissingInput(String, String),
+    TooManyInputs(String, usize, usize),
+    UnknownParameter(String),
+    UnsupportedFormat(String, NodeFormat),
+    UnknownPort(String, String),
+    InvalidFramerate(String),
+    Shader(String, UniformLayoutError),
+}
+
+impl Display for GraphError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GraphError::IoError(e) => write!(f, "IoError: {}", e),
+            GraphError::JsonError(e) => write!(f, "JsonError: {}", e),
+            GraphError::UnknownNodeType(t) => write!(f, "unknown node type '{}'", t),
+            GraphError::UnknownNode(n) => write!(f, "unknown node '{}'", n),
+            GraphError::DuplicateNode(n) => write!(f, "node '{}' is defined more than once", n),
+            GraphError::Cycle(n) => write!(f, "node '{}' is part of a cycle", n),
+            GraphError::InvalidOption(n, o, e) => {
+                write!(f, "invalid option '{}' of node '{}': {}", o, n, e)
+            }
+            GraphError::InvalidSlot(n, s) => write!(f, "node '{}' has no input slot {}", n, s),
+            GraphError::RootNode(n) => write!(f, "node '{}' is the root node", n),
+            GraphError::OutputNode(n) => write!(f, "node '{}' is an output node", n),
+            GraphError::InvalidSize(n) => write!(f, "invalid size or scale of node '{}'", n),
+            GraphError::DuplicateOutput(n) => {
+                write!(f, "output '{}' is defined more than once", n)
+            }
+            GraphError::MissingInput(n, p) => {
+                write!(f, "required input '{}' of node '{}' is not connected", p, n)
+            }
+            GraphError::TooManyInputs(n, c, m) => {
+                write!(f, "node '{}' has {} inputs but accepts at most {}", n, c, m)
+            }
+            GraphError::UnknownParameter(p) => write!(f, "unknown parameter '{}'", p),
+            GraphError::UnsupportedFormat(n, t) => {
+                write!(
+                    f,
+                    "format {:?} of node '{}' is not supported by the device",
+                    t, n
+                )
+            }
+            GraphError::UnknownPort(n, p) => write!(f, "node '{}' has no input port '{}'", n, p),
+            GraphError::InvalidFramerate(n) => write!(f, "invalid framerate of stream '{}'", n),
+            GraphError::Shader(n, e) => write!(f, "invalid shader of node '{}': {}", n, e),
+        }
+    }
+}
+
+impl Error for GraphError {}
+
+/// describes a single [TextureNode] inside a [GraphDescription]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct NodeDescription {
+    /// name of the node, used as base for the OSC addresses
+    pub name: String,
+    /// type of the node e.g. `"wave"` or `"fader"`
+    #[serde(rename = "type")]
+    pub node_type: String,
+    /// names of the input nodes, in input order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub inputs: Vec<String>,
+    /// texture size of this node, uses the size of the graph if not set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<[u32; 2]>,
+    /// texture size relative to the size of the graph, e.g. `0.25` for expensive nodes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scale: Option<f32>,
+    /// node type specific options
+    #[serde(default, skip_serializing_if = "NodeOptions::is_empty")]
+    pub options: NodeOptions,
+    /// node whose output of the previous frame is passed to this node, e.g. to a `feedback` node
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub feedback: Option<String>,
+    /// texture format of this node, uses the format of the graph if not set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub format: Option<NodeFormat>,
+    /// sampling of the input ports, by port name, the ports not listed use their default
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub samplers: BTreeMap<String, SamplerConfig>,
+}
+
+fn default_framerate() -> i32 {
+    60
+}
+
+fn default_stream_name() -> String {
+    "visgen_graph".to_string()
+}
+
+impl NodeDescription {
+    /// texture size of the node, `texture_size` is the size of the graph
+    pub fn output_size(&self, texture_size: [u32; 2]) -> [u32; 2] {
+        match (self.size, self.scale) {
+            (Some(size), _) => size,
+            (None, Some(scale)) => [
+                ((texture_size[0] as f32 * scale).round() as u32).max(1),
+                ((texture_size[1] as f32 * scale).round() as u32).max(1),
+            ],
+            (None, None) => texture_size,
+        }
+    }
+}
+
+/// describes an additional output of the graph, that is sent as its own NDI stream
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct OutputDescription {
+    /// name of the NDI stream
+    pub name: String,
+    /// name of the node that is sent
+    pub node: String,
+    /// framerate of the NDI stream
+    #[serde(default = "default_framerate")]
+    pub framerate: i32,
+    /// resolution of the NDI stream, uses the size of the node if not set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resolution: Option<[u32; 2]>,
+}
+
+/// describes the NDI stream of the root node
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct StreamDescription {
+    /// name of the NDI stream
+    #[serde(default = "default_stream_name")]
+    pub name: String,
+    /// framerate of the NDI stream, rendered frames in between are not sent
+    #[serde(default = "default_framerate")]
+    pub framerate: i32,
+}
+
+impl Default for StreamDescription {
+    fn default() -> Self {
+        Self {
+            name: default_stream_name(),
+            framerate: default_framerate(),
+        }
+    }
+}
+
+impl StreamDescription {
+    /// true if nothing differs from the default stream
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+///
+/// Describes a reusable subgraph, instances are used like a node of the group type
+///
+/// the inner nodes are named `<instance>/<node>`, so their parameters are at e.g. `/fog1/perlin/scalex`.
+/// `parameters` exposes inner parameters under the instance, e.g. `"density": "perlin/scalex"` as `/fog1/density`
+///
+/// ```json
+/// "groups": {
+///   "fog": {
+///     "inputs": ["background"],
+///     "root": "mask",
+///     "nodes": [
+///       { "name": "perlin", "type": "perlin" },
+///       { "name": "ramp", "type": "color_ramp", "inputs": ["perlin"] },
+///       { "name": "mask", "type": "mask", "inputs": ["background", "ramp", "perlin"] }
+///     ],
+///     "parameters": { "density": "perlin/scalex" }
+///   }
+/// }
+/// ```
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct GroupDescription {
+    /// names of the input ports, inner nodes use them like node names
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub inputs: Vec<String>,
+    /// name of the inner node used as output of the group
+    pub root: String,
+    /// inner nodes, sizes are relative to the size of the group instance
+    pub nodes: Vec<NodeDescription>,
+    /// exposed parameters, name below the instance mapped to the inner parameter path
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub parameters: BTreeMap<String, String>,
+}
+
+impl GroupDescription {
+    /// check that the group is complete, like [GraphDescription::validate]
+    ///
+    /// input ports and inner nodes share their names
+    pub fn validate(&self) -> Result<(), GraphError> {
+        validate_nodes(&self.nodes, &self.inputs, &self.root)?;
+        if let Some(input) = self.inputs.iter().find(|&i| i == &self.root) {
+            return Err(GraphError::RootNode(input.clone()));
+        }
+        Ok(())
+    }
+}
+
+/// describes a complete [TextureTree]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct GraphDescription {
+    /// default texture size of all nodes
+    pub texture_size: [u32; 2],
+    /// default texture format of all nodes
+    #[serde(default, skip_serializing_if = "NodeFormat::is_default")]
+    pub format: NodeFormat,
+    /// name of the output node
+    pub root: String,
+    /// all nodes of the graph
+    pub nodes: Vec<NodeDescription>,
+    /// NDI stream of the root node
+    #[serde(default, skip_serializing_if = "StreamDescription::is_default")]
+    pub stream: StreamDescription,
+    /// additional outputs
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub outputs: Vec<OutputDescription>,
+    /// reusable subgraphs, their names can be used as node types
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub groups: BTreeMap<String, GroupDescription>,
+}
+
+impl GraphDescription {
+    /// read a graph description from a JSON file
+    pub fn load(path: &Path) -> Result<Self, GraphError> {
+        let file = File::open(path).map_err(GraphError::IoError)?;
+        serde_json::from_reader(file).map_err(GraphError::JsonError)
+    }
+
+    /// write the graph description to a JSON file
+    pub fn store(&self, path: &Path) -> Result<(), GraphError> {
+        let file = File::create(path).map_err(GraphError::IoError)?;
+        serde_json::to_writer_pretty(file, self).map_err(GraphError::JsonError)
+    }
+
+    /// describe a running [TextureTree]
+    ///
+    /// all nodes with a known [NodeInfo] are described, also the ones that are not rendered.
+    /// `texture_size` and `format` are the defaults of the graph, the tree does not know
+    /// the stream of the root, it is set to the default.
+    /// nodes keep their creation order, so the parameter order stays the same for stored programs
+    pub fn from_tree(tree: &TextureTree, texture_size: [u32; 2], format: NodeFormat) -> Self {
+        let name_of = |id: NodeId| tree.node_info(id).map(|i| i.name.clone());
+
+        let nodes = tree
+            .all_nodes()
+            .filter_map(|id| {
+                tree.node_info(id).map(|info| NodeDescription {
+                    name: info.name.clone(),
+                    node_type: info.node_type.clone(),
+                    inputs: tree.inputs(id).into_iter().filter_map(name_of).collect(),
+                    size: if info.size == texture_size {
+                        None
+                    } else {
+                        Some(info.size)
+                    },
+                    scale: None,
+                    options: info.options.clone(),
+                    feedback: tree.feedback_source(id).and_then(name_of),
+                    format: if info.format == format {
+                        None
+                    } else {
+                        Some(info.format)
+                    },
+                    samplers: tree.samplers(id).cloned().unwrap_or_default(),
+                })
+            })
+            .collect();
+
+        let outputs = tree
+            .outputs()
+            .filter_map(|(id, output)| {
+                name_of(id).map(|node| OutputDescription {
+                    node,
+                    ..output.clone()
+                })
+            })
+            .collect();
+
+        Self {
+            texture_size,
+            format,
+            root: name_of(tree.root()).unwrap_or_default(),
+            nodes,
+            stream: StreamDescription::default(),
+            outputs,
+            groups: BTreeMap::new(),
+        }
+    }
+
+    /// check that the description is complete
+    ///
+    /// - node and output names are unique
+    /// - the framerates of the streams are positive
+    /// - sizes are not zero, only one of `size` and `scale` is set
+    /// - all inputs, the root and the output nodes exist
+    /// - all groups are valid and do not contain themselves
+    ///
+    /// cycles and inputs that do not match the [crate::InputPort]s of a node
+    /// are detected when the [TextureTree] is built
+    pub fn validate(&self) -> Result<(), GraphError> {
+        let names = validate_nodes(&self.nodes, &[], &self.root)?;
+
+        if self.stream.framerate <= 0 {
+            return Err(GraphError::InvalidFramerate(self.stream.name.clone()));
+        }
+        let mut outputs = HashSet::new();
+        for output in &self.outputs {
+            if !outputs.insert(output.name.as_str()) {
+                return Err(GraphError::DuplicateOutput(output.name.clone()));
+            }
+            if output.framerate <= 0 {
+                return Err(GraphError::InvalidFramerate(output.name.clone()));
+            }
+            if !names.contains(output.node.as_str()) {
+                return Err(GraphError::UnknownNode(output.node.clone()));
+            }
+        }
+
+        for (name, group) in &self.groups {
+            group.validate()?;
+            self.check_group_recursion(name, &mut Vec::new())?;
+        }
+
+        Ok(())
+    }
+
+    /// device features needed by the formats of the graph, its nodes and groups
+    ///
+    /// border samplers are always supported, they are the default of the `feedback` port
+    /// and nodes can be added while the graph is running
+    pub fn required_features(&self) -> Features {
+        let nodes = self
+            .nodes
+            .iter()
+            .chain(self.groups.values().flat_map(|group| &group.nodes));
+        nodes.filter_map(|node| node.format).fold(
+            self.format.required_features() | Features::ADDRESS_MODE_CLAMP_TO_BORDER,
+            |features, format| features | format.required_features(),
+        )
+    }
+
+    /// depth first search through the group types used inside a group
+    fn check_group_recursion<'a>(
+        &'a self,
+        group: &'a str,
+        visiting: &mut Vec<&'a str>,
+    ) -> Result<(), GraphError> {
+        if visiting.contains(&group) {
+            return Err(GraphError::Cycle(group.to_string()));
+        }
+        visiting.push(group);
+        for node in &self.groups[group].nodes {
+            if self.groups.contains_key(&node.node_type) {
+                self.check_group_recursion(&node.node_type, visiting)?;
+            }
+        }
+        visiting.pop();
+        Ok(())
+    }
+
+    /// create all nodes using the `registry` and build a runnable [TextureTree]
+    ///
+    /// the groups of the graph are added to the `registry`, so they can also be added later.
+    /// the parameters of all nodes and their [crate::NodeControls] are registered inside the `store`
+    pub fn build(
+        &self,
+        registry: &mut NodeRegistry,
+        device: &Device,
+        store: &mut ParameterStore,
+    ) -> Result<TextureTree, GraphError> {
+        self.validate()?;
+        for (name, group) in &self.groups {
+            registry.register_group(name, group.clone());
+        }
+
+        let mut nodes = NodeBuilder::new(registry, device, self.texture_size, self.format, "");
+        nodes.build(&self.nodes, store)?;
+
+        let root = nodes.ids[self.root.as_str()];
+        let outputs: Vec<NodeId> = self
+            .outputs
+            .iter()
+            .map(|output| nodes.ids[output.node.as_str()])
+            .collect();
+        let mut tree = nodes.into_tree(root)?;
+        for (&id, output) in outputs.iter().zip(&self.outputs) {
+            tree.add_output(id, output.clone())?;
+        }
+        tree.register_controls(store);
+        Ok(tree)
+    }
+}
+
+/// checks the nodes of a graph or group, `ports` are additional names that can be used as input
+///
+/// returns all names
+fn validate_nodes<'a>(
+    nodes: &'a [NodeDescription],
+    ports: &'a [String],
+    root: &str,
+) -> Result<HashSet<&'a str>, GraphError> {
+    let mut names = HashSet::new();
+    for name in ports.iter().chain(nodes.iter().map(|n| &n.name)) {
+        if !names.insert(name.as_str()) {
+            return Err(GraphError::DuplicateNode(name.clone()));
+        }
+    }
+
+    for node in nodes {
+        let zero_size = matches!(node.size, Some([w, h]) if w == 0 || h == 0);
+        let invalid_scale = matches!(node.scale, Some(s) if s <= 0.0 || !s.is_finite());
+        if zero_size || invalid_scale || (node.size.is_some() && node.scale.is_some()) {
+            return Err(GraphError::InvalidSize(node.name.clone()));
+        }
+        if let Some(input) = node.inputs.iter().find(|i| !names.contains(i.as_str())) {
+            return Err(GraphError::UnknownNode(input.clone()));
+        }
+        match &node.feedback {
+            Some(source) if !names.contains(source.as_str()) => {
+                return Err(GraphError::UnknownNode(source.clone()));
+            }
+            _ => {}
+        }
+    }
+
+    if !names.contains(root) {
+        return Err(GraphError::UnknownNode(root.to_string()));
+    }
+    Ok(names)
+}
+
+///
+/// Creates described nodes inside an arena, used for graphs and groups
+///
+pub(crate) struct NodeBuilder<'a> {
+    registry: &'a NodeRegistry,
+    device: &'a Device,
+    texture_size: [u32; 2],
+    /// format of the nodes without their own format
+    format: NodeFormat,
+    /// prepended to all node names, e.g. `fog1/`
+    prefix: String,
+    arena: Arena<Box<dyn TextureNode>>,
+    /// nodes by their name without prefix
+    pub ids: HashMap<String, NodeId>,
+    inputs: HashMap<NodeId, Vec<NodeId>>,
+    info: HashMap<NodeId, NodeInfo>,
+    /// feedback node and its source
+    feedback: Vec<(NodeId, NodeId)>,
+    /// sampling of the ports of a node
+    samplers: Vec<(NodeId, String, SamplerConfig)>,
+}
+
+impl<'a> NodeBuilder<'a> {
+    pub fn new(
+        registry: &'a NodeRegistry,
+        device: &'a Device,
+        texture_size: [u32; 2],
+        format: NodeFormat,
+        prefix: &str,
+    ) -> Self {
+        Self {
+            registry,
+            device,
+            texture_size,
+            format,
+            prefix: prefix.to_string(),
+            arena: Arena::new(),
+            ids: HashMap::new(),
+            inputs: HashMap::new(),
+            info: HashMap::new(),
+            feedback: Vec::new(),
+            samplers: Vec::new(),
+        }
+    }
+
+    /// add a node that is not described, e.g. the input of a group
+    pub fn add(&mut self, name: &str, node: Box<dyn TextureNode>) -> NodeId {
+        let id = self.arena.new_node(node);
+        self.ids.insert(name.to_string(), id);
+        id
+    }
+
+    /// create the described nodes and their inputs, the nodes must be validated
+    pub fn build(
+        &mut self,
+        nodes: &[NodeDescription],
+        store: &mut ParameterStore,
+    ) -> Result<(), GraphError> {
+        if let Some(node) = nodes.iter().find(|n| !self.registry.contains(&n.node_type)) {
+            return Err(GraphError::UnknownNodeType(node.node_type.clone()));
+        }
+
+        for node in nodes {
+            let name = format!("{}{}", self.prefix, node.name);
+            let size = node.output_size(self.texture_size);
+            let format = node.format.unwrap_or(self.format);
+            let ctx = BuildContext {
+                name: name.clone(),
+                size,
+                format,
+                store: &mut *store,
+                device: self.device,
+                options: &node.options,
+            };
+            let id = self.add(&node.name, self.registry.build(&node.node_type, ctx)?);
+            self.info.insert(
+                id,
+                NodeInfo {
+                    name,
+                    node_type: node.node_type.clone(),
+                    size,
+                    format,
+                    options: node.options.clone(),
+                },
+            );
+        }
+
+        for node in nodes {
+            let node_inputs = node.inputs.iter().map(|i| self.ids[i]).collect();
+            self.inputs.insert(self.ids[&node.name], node_inputs);
+            if let Some(source) = &node.feedback {
+                self.feedback.push((self.ids[&node.name], self.ids[source]));
+            }
+            for (port, sampler) in &node.samplers {
+                let id = self.ids[&node.name];
+                self.samplers.push((id, port.clone(), *sampler));
+            }
+        }
+        Ok(())
+    }
+
+    /// build the [TextureTree] of all created nodes
+    pub fn into_tree(self, root: NodeId) -> Result<TextureTree, GraphError> {
+        let mut tree = TextureTree::from_edges(self.arena, self.inputs, root, self.info)?;
+        for (node, source) in self.feedback {
+            tree.set_feedback(node, Some(source))?;
+        }
+        for (node, port, sampler) in self.samplers {
+            tree.set_sampler(self.device, node, &port, sampler)?;
+        }
+        Ok(tree)
+    }
+}
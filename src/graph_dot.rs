@@ -0,0 +1,149 @@
+///
+/// # Graph DOT export
+/// Describes a running [TextureTree] in the [Graphviz](https://graphviz.org) DOT language,
+/// to document or debug a show.
+///
+/// ```dot
+/// digraph visgen_graph {
+///     node [shape=box, fontname="monospace"];
+///     "fader" [label="fader: fader (root)\l\lfader = 0.5\l", peripheries=2];
+///     "wave" [label="wave: wave\l\lspeed = 0.1, 0\l"];
+///     "wave" -> "fader" [label="0: first"];
+/// }
+/// ```
+///
+/// Every node shows its name, type and the current values of its parameters.
+/// Nodes that are not needed by an output are dotted.
+/// Edges are labeled with the input slot and the name of the [crate::InputPort],
+/// feedback links are dashed.
+///
+use crate::{ParameterStore, TextureTree};
+use indextree::NodeId;
+use nannou_osc::Type;
+use std::collections::HashSet;
+use std::fmt::Write;
+
+impl TextureTree {
+    /// describe the graph in the DOT language, using the current values of `store`
+    ///
+    /// all nodes are described, also the ones that are not rendered
+    pub fn to_dot(&self, store: &ParameterStore) -> String {
+        let rendered: HashSet<NodeId> = self.nodes().collect();
+        let mut ids: Vec<NodeId> = self.all_nodes().collect();
+        ids.sort_by_key(|&id| self.node_name(id));
+
+        let mut dot = String::new();
+        writeln!(dot, "digraph visgen_graph {{").unwrap();
+        writeln!(dot, "    node [shape=box, fontname=\"monospace\"];").unwrap();
+
+        for &id in &ids {
+            writeln!(
+                dot,
+                "    {} [{}];",
+                quote(&self.node_name(id)),
+                self.dot_node(id, store, rendered.contains(&id))
+            )
+            .unwrap();
+        }
+
+        for &id in &ids {
+            let ports = port_names(self, id);
+            for (slot, input) in self.inputs(id).into_iter().enumerate() {
+                let label = match ports.get(slot) {
+                    Some(port) => format!("{}: {}", slot, port),
+                    None => slot.to_string(),
+                };
+                writeln!(
+                    dot,
+                    "    {} -> {} [label={}];",
+                    quote(&self.node_name(input)),
+                    quote(&self.node_name(id)),
+                    quote(&label)
+                )
+                .unwrap();
+            }
+            if let Some(source) = self.feedback_source(id) {
+                writeln!(
+                    dot,
+                    "    {} -> {} [label=\"feedback\", style=dashed];",
+                    quote(&self.node_name(source)),
+                    quote(&self.node_name(id))
+                )
+                .unwrap();
+            }
+        }
+
+        writeln!(dot, "}}").unwrap();
+        dot
+    }
+
+    /// attributes of a single node, `rendered` is false if no output needs the node
+    fn dot_node(&self, id: NodeId, store: &ParameterStore, rendered: bool) -> String {
+        let name = self.node_name(id);
+        let mut title = match self.node_info(id) {
+            Some(info) => format!("{}: {}", name, info.node_type),
+            None => name.clone(),
+        };
+        if id == self.root() {
+            title.push_str(" (root)");
+        }
+
+        let mut lines = vec![title];
+        for (_, output) in self.outputs().filter(|&(node, _)| node == id) {
+            lines.push(format!("output {}", output.name));
+        }
+
+        let prefix = format!("/{}", name);
+        let parameters = store.values_below(&prefix);
+        if !parameters.is_empty() {
+            lines.push(String::new());
+        }
+        for (path, values) in parameters {
+            let values: Vec<String> = values.iter().map(format_value).collect();
+            let path = path.strip_prefix(&format!("{}/", prefix)).unwrap_or(&path);
+            lines.push(format!("{} = {}", path, values.join(", ")));
+        }
+
+        // `\l` ends a left aligned line
+        let label: String = lines.iter().map(|l| format!("{}\\l", escape(l))).collect();
+        let mut attributes = format!("label=\"{}\"", label);
+        if id == self.root() {
+            attributes.push_str(", peripheries=2");
+        }
+        if !rendered {
+            attributes.push_str(", style=dotted");
+        }
+        attributes
+    }
+}
+
+/// the port name of every input slot of a node
+fn port_names(tree: &TextureTree, id: NodeId) -> Vec<String> {
+    let inputs = tree.inputs(id).len();
+    let mut names = Vec::new();
+    for port in tree.input_ports(id) {
+        let remaining = inputs.saturating_sub(names.len());
+        for _ in 0..port.count.min(remaining) {
+            names.push(port.name.clone());
+        }
+    }
+    names
+}
+
+fn format_value(value: &Type) -> String {
+    match value {
+        Type::Int(i) => i.to_string(),
+        Type::Float(f) => f.to_string(),
+        Type::Bool(b) => b.to_string(),
+        Type::String(s) => format!("{:?}", s),
+        other => format!("{:?}", other),
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn quote(text: &str) -> String {
+    format!("\"{}\"", escape(text))
+}
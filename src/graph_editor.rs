@@ -0,0 +1,236 @@
+use crate::{
+    BuildContext, GraphDescription, GraphError, NodeFormat, NodeInfo, NodeOptions, NodeRegistry,
+    ParameterStore, SamplerConfig, StreamDescription, TextureTree,
+};
+use nannou::wgpu::Device;
+use nannou_osc::{Message, Type};
+use std::path::Path;
+
+///
+/// Edits a running [TextureTree] using OSC messages
+///
+/// # OSC Commands
+///
+/// | Address              | Arguments                           | Description                                  |
+/// |----------------------|-------------------------------------|----------------------------------------------|
+/// | `/graph/add`         | `type: String, name: String, [width: i32, height: i32]` | create a new node and register its parameters|
+/// | `/graph/connect`     | `node: String, input: String, [slot: i32]` | use `input` as input `slot` of `node`, appends without slot |
+/// | `/graph/disconnect`  | `node: String, slot: i32`           | remove an input of a node                    |
+/// | `/graph/remove`      | `name: String`                      | remove a node and unregister its parameters  |
+/// | `/graph/root`        | `name: String`                      | use a node as output                         |
+/// | `/graph/feedback`    | `node: String, [source: String]`    | pass the previous frame of `source` to `node`, removes it without source |
+/// | `/graph/sampler`     | `node: String, port: String, address: String, filter: String, [mipmaps: i32]` | change the sampling of an input port, e.g. `repeat`, `nearest`, see [SamplerConfig] |
+/// | `/graph/store`       | `path: String`                      | store the graph as JSON file                 |
+/// | `/graph/dump_dot`    | `path: String`                      | store the graph with its parameter values as Graphviz DOT file |
+///
+/// New nodes also get their [crate::NodeControls] (`bypass`, `mute`, `solo`).
+/// Edits are applied directly, so they take effect with the next frame.
+/// New parameters are not announced in an already running OSCQuery service.
+pub struct GraphEditor {
+    registry: NodeRegistry,
+    texture_size: [u32; 2],
+    format: NodeFormat,
+    stream: StreamDescription,
+}
+
+impl GraphEditor {
+    /// create a new editor
+    /// - `registry`: used to create new nodes
+    /// - `texture_size`: texture size of new nodes without a size
+    /// - `format`: texture format of new nodes
+    /// - `stream`: NDI stream of the root, kept when the graph is stored
+    pub fn new(
+        registry: NodeRegistry,
+        texture_size: [u32; 2],
+        format: NodeFormat,
+        stream: StreamDescription,
+    ) -> Self {
+        Self {
+            registry,
+            texture_size,
+            format,
+            stream,
+        }
+    }
+
+    /// apply osc massages to the graph
+    pub fn update_osc(
+        &mut self,
+        tree: &mut TextureTree,
+        store: &mut ParameterStore,
+        device: &Device,
+        msg: &Message,
+    ) {
+        if !msg.addr.starts_with("/graph/") {
+            return;
+        }
+
+        if let Err(e) = self.apply(tree, store, device, msg) {
+            println!("graph_error: {} {}", msg.addr, e);
+        }
+    }
+
+    fn apply(
+        &mut self,
+        tree: &mut TextureTree,
+        store: &mut ParameterStore,
+        device: &Device,
+        msg: &Message,
+    ) -> Result<(), GraphError> {
+        let args = msg.args.clone().unwrap_or_default();
+
+        match msg.addr.as_str() {
+            "/graph/add" => {
+                if let (Some(node_type), Some(name)) = (string_arg(&args, 0), string_arg(&args, 1))
+                {
+                    let size = match (int_arg(&args, 2), int_arg(&args, 3)) {
+                        (Some(w), Some(h)) if w > 0 && h > 0 => [w as u32, h as u32],
+                        (None, None) => self.texture_size,
+                        _ => return Err(GraphError::InvalidSize(name)),
+                    };
+                    self.add(tree, store, device, node_type, name, size)?;
+                }
+            }
+            "/graph/connect" => {
+                if let (Some(node), Some(input)) = (string_arg(&args, 0), string_arg(&args, 1)) {
+                    let node = find(tree, &node)?;
+                    let input = find(tree, &input)?;
+                    let slot = int_arg(&args, 2)
+                        .map(|s| s as usize)
+                        .unwrap_or_else(|| tree.inputs(node).len());
+                    tree.connect(node, input, slot)?;
+                }
+            }
+            "/graph/disconnect" => {
+                if let (Some(node), Some(slot)) = (string_arg(&args, 0), int_arg(&args, 1)) {
+                    let node = find(tree, &node)?;
+                    tree.disconnect(node, slot as usize)?;
+                }
+            }
+            "/graph/remove" => {
+                if let Some(name) = string_arg(&args, 0) {
+                    let node = find(tree, &name)?;
+                    tree.remove_node(node)?;
+                    store.unregister(&format!("/{}", name));
+                }
+            }
+            "/graph/root" => {
+                if let Some(name) = string_arg(&args, 0) {
+                    let node = find(tree, &name)?;
+                    tree.set_root(node)?;
+                }
+            }
+            "/graph/feedback" => {
+                if let Some(node) = string_arg(&args, 0) {
+                    let node = find(tree, &node)?;
+                    let source = match string_arg(&args, 1) {
+                        Some(source) => Some(find(tree, &source)?),
+                        None => None,
+                    };
+                    tree.set_feedback(node, source)?;
+                }
+            }
+            "/graph/sampler" => {
+                if let (Some(name), Some(port), Some(address), Some(filter)) = (
+                    string_arg(&args, 0),
+                    string_arg(&args, 1),
+                    string_arg(&args, 2),
+                    string_arg(&args, 3),
+                ) {
+                    let node = find(tree, &name)?;
+                    let sampler = SamplerConfig {
+                        address: parse_arg(&name, "address", address)?,
+                        filter: parse_arg(&name, "filter", filter)?,
+                        mipmaps: int_arg(&args, 4).unwrap_or(0) != 0,
+                    };
+                    tree.set_sampler(device, node, &port, sampler)?;
+                }
+            }
+            "/graph/store" => {
+                if let Some(path) = string_arg(&args, 0) {
+                    let mut graph =
+                        GraphDescription::from_tree(tree, self.texture_size, self.format);
+                    graph.groups = self.registry.groups().clone();
+                    graph.stream = self.stream.clone();
+                    graph.store(Path::new(&path))?;
+                }
+            }
+            "/graph/dump_dot" => {
+                if let Some(path) = string_arg(&args, 0) {
+                    std::fs::write(path, tree.to_dot(store)).map_err(GraphError::IoError)?;
+                }
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    /// create a new node using the registry
+    fn add(
+        &mut self,
+        tree: &mut TextureTree,
+        store: &mut ParameterStore,
+        device: &Device,
+        node_type: String,
+        name: String,
+        size: [u32; 2],
+    ) -> Result<(), GraphError> {
+        if tree.find(&name).is_some() {
+            return Err(GraphError::DuplicateNode(name));
+        }
+
+        let options = NodeOptions::new();
+        let ctx = BuildContext {
+            name: name.clone(),
+            size,
+            format: self.format,
+            store: &mut *store,
+            device,
+            options: &options,
+        };
+
+        let node = match self.registry.build(&node_type, ctx) {
+            Ok(node) => node,
+            Err(e) => {
+                // remove parameters that were created before the error
+                store.unregister(&format!("/{}", name));
+                return Err(e);
+            }
+        };
+
+        let info = NodeInfo {
+            name,
+            node_type,
+            size,
+            format: self.format,
+            options,
+        };
+        tree.add_node(node, info)?;
+        tree.register_controls(store);
+        Ok(())
+    }
+}
+
+fn find(tree: &TextureTree, name: &str) -> Result<indextree::NodeId, GraphError> {
+    tree.find(name)
+        .ok_or_else(|| GraphError::UnknownNode(name.to_string()))
+}
+
+fn string_arg(args: &[Type], i: usize) -> Option<String> {
+    args.get(i).and_then(|a| a.clone().string())
+}
+
+fn int_arg(args: &[Type], i: usize) -> Option<i32> {
+    args.get(i).and_then(|a| a.clone().int())
+}
+
+/// parse a string argument like the value in a [GraphDescription], e.g. `"mirror"`
+fn parse_arg<T: serde::de::DeserializeOwned>(
+    node: &str,
+    option: &str,
+    value: String,
+) -> Result<T, GraphError> {
+    serde_json::from_value(serde_json::Value::String(value))
+        .map_err(|e| GraphError::InvalidOption(node.to_string(), option.to_string(), e))
+}
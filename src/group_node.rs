@@ -0,0 +1,138 @@
+use crate::graph_description::NodeBuilder;
+use crate::util::resample::Resampler;
+use crate::{
+    BuildContext, Fallback, GraphError, GroupDescription, InputPort, NodeRegistry, ParameterStore,
+    RenderContext, SamplerConfig, TextureNode, TextureTree,
+};
+use indextree::NodeId;
+use nannou::wgpu::{CommandEncoder, Device, TextueSnapshot, TextureCapturer, TextureView};
+
+///
+/// Instance of a [GroupDescription], an inner [TextureTree] used as a single node
+///
+/// every input port is an inner node, that copies the outer input to the size and format of the group,
+/// the sampling of the ports is used for these copies.
+/// inner nodes without their own format use the format of the instance.
+/// inner nodes are named `<instance>/<node>`, their parameters and [crate::NodeControls]
+/// are at e.g. `/fog1/perlin/scalex` and `/fog1/perlin/bypass`.
+/// exposed parameters are aliases inside the [ParameterStore], e.g. `/fog1/density`
+pub struct GroupNode {
+    tree: TextureTree,
+    ports: Vec<InputPort>,
+    /// inner input nodes, in port order
+    inputs: Vec<NodeId>,
+}
+
+impl GroupNode {
+    /// create the inner nodes of `group` using the `registry`
+    pub fn new(
+        ctx: BuildContext,
+        group: &GroupDescription,
+        registry: &NodeRegistry,
+    ) -> Result<Self, GraphError> {
+        group.validate()?;
+
+        let prefix = format!("{}/", ctx.name);
+        let format = ctx.format.texture_format();
+        let mut nodes = NodeBuilder::new(registry, ctx.device, ctx.size, ctx.format, &prefix);
+
+        let mut ports = Vec::new();
+        let mut inputs = Vec::new();
+        for port in &group.inputs {
+            let resampler = Resampler::new(ctx.device, ctx.size, format)
+                .map_err(|e| GraphError::Shader(ctx.name.clone(), e))?;
+            let input = GroupInputNode { resampler };
+            inputs.push(nodes.add(port, Box::new(input)));
+            ports.push(InputPort::optional(port, Fallback::Black));
+        }
+
+        nodes.build(&group.nodes, ctx.store)?;
+        let root = nodes.ids[group.root.as_str()];
+        let mut tree = nodes.into_tree(root)?;
+        tree.register_controls(ctx.store);
+
+        for (alias, target) in &group.parameters {
+            let alias = format!("/{}{}", prefix, alias);
+            let target = format!("/{}{}", prefix, target);
+            if !ctx.store.add_alias(&alias, &target) {
+                return Err(GraphError::UnknownParameter(target));
+            }
+        }
+
+        Ok(Self {
+            tree,
+            ports,
+            inputs,
+        })
+    }
+}
+
+impl TextureNode for GroupNode {
+    fn update(
+        &mut self,
+        ctx: &RenderContext,
+        encoder: &mut CommandEncoder,
+        store: &ParameterStore,
+        input: Vec<TextureView>,
+    ) {
+        for (&id, texture) in self.inputs.iter().zip(input) {
+            if let Some(node) = self.tree.node_mut(id) {
+                node.update(ctx, encoder, store, vec![texture]);
+            }
+        }
+        self.tree.record(ctx, encoder, store);
+    }
+
+    fn output(&self) -> TextureView {
+        self.tree.output()
+    }
+
+    fn snapshot(&self, ctx: &RenderContext, texture_capturer: &TextureCapturer) -> TextueSnapshot {
+        self.tree.snapshot(ctx, texture_capturer)
+    }
+
+    fn input_ports(&self) -> Vec<InputPort> {
+        self.ports.clone()
+    }
+
+    fn set_samplers(&mut self, device: &Device, samplers: &[SamplerConfig]) {
+        for (&id, sampler) in self.inputs.iter().zip(samplers) {
+            if let Some(node) = self.tree.node_mut(id) {
+                node.set_samplers(device, std::slice::from_ref(sampler));
+            }
+        }
+    }
+}
+
+/// input port inside a group, the texture is set by the [GroupNode] before the inner tree is updated
+struct GroupInputNode {
+    resampler: Resampler,
+}
+
+impl TextureNode for GroupInputNode {
+    fn update(
+        &mut self,
+        ctx: &RenderContext,
+        encoder: &mut CommandEncoder,
+        _store: &ParameterStore,
+        mut input: Vec<TextureView>,
+    ) {
+        // the inner tree updates the node without input, the last copy is kept
+        if let Some(texture) = input.pop() {
+            self.resampler.update(ctx, encoder, texture);
+        }
+    }
+
+    fn output(&self) -> TextureView {
+        self.resampler.output()
+    }
+
+    fn snapshot(&self, ctx: &RenderContext, texture_capturer: &TextureCapturer) -> TextueSnapshot {
+        self.resampler.snapshot(ctx, texture_capturer)
+    }
+
+    fn set_samplers(&mut self, device: &Device, samplers: &[SamplerConfig]) {
+        self.resampler
+            .set_sampler(device, samplers.first().copied().unwrap_or_default());
+    }
+}
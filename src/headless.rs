@@ -0,0 +1,233 @@
+//!
+//! Renders a graph without a window, e.g. on a render server or in CI
+//!
+//! the graph is updated at a fixed framerate and sent over NDI,
+//! the output can also be written as PNG files.
+//!
+//! ```text
+//! headless [graph] [--fps <n>] [--fixed-step] [--frames <n>] [--png <dir>] [--no-ndi]
+//! ```
+use nannou::wgpu;
+use nannou_osc as osc;
+use std::num::NonZeroU32;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use visgen_graph::program::program::ProgramManager;
+use visgen_graph::util::file_output::FileOutput;
+use visgen_graph::util::headless_device::HeadlessDevice;
+use visgen_graph::util::ndi_output::NdiOutput;
+use visgen_graph::util::ndi_stream::NdiStream;
+use visgen_graph::{
+    Clock, GraphDescription, GraphEditor, NodeRegistry, OutputStage, ParameterStore,
+};
+
+const PORT: u16 = 6060;
+const DEFAULT_GRAPH: &str = "graphs/default.json";
+const DEFAULT_FPS: u32 = 60;
+const FPS_FLAG: &str = "--fps";
+const FIXED_STEP_FLAG: &str = "--fixed-step";
+const FRAMES_FLAG: &str = "--frames";
+const PNG_FLAG: &str = "--png";
+const NO_NDI_FLAG: &str = "--no-ndi";
+
+/// command line arguments, `--fps 0` is rejected
+struct Args {
+    graph_path: String,
+    fps: NonZeroU32,
+    fixed_step: bool,
+    frames: Option<u64>,
+    png_dir: Option<PathBuf>,
+    ndi: bool,
+}
+
+impl Args {
+    fn parse() -> Self {
+        let mut args = Args {
+            graph_path: DEFAULT_GRAPH.to_string(),
+            fps: NonZeroU32::new(DEFAULT_FPS).unwrap(),
+            fixed_step: false,
+            frames: None,
+            png_dir: None,
+            ndi: true,
+        };
+        let mut graph_path = None;
+        let mut iter = std::env::args().skip(1);
+        while let Some(arg) = iter.next() {
+            match arg.as_str() {
+                FPS_FLAG => args.fps = parse_number(FPS_FLAG, iter.next()),
+                FIXED_STEP_FLAG => args.fixed_step = true,
+                FRAMES_FLAG => args.frames = Some(parse_number(FRAMES_FLAG, iter.next())),
+                PNG_FLAG => args.png_dir = iter.next().map(PathBuf::from),
+                NO_NDI_FLAG => args.ndi = false,
+                _ if graph_path.is_none() => graph_path = Some(arg),
+                _ => println!("unknown argument {}", arg),
+            }
+        }
+        if let Some(path) = graph_path {
+            args.graph_path = path;
+        }
+        args
+    }
+}
+
+fn parse_number<T: std::str::FromStr>(flag: &str, value: Option<String>) -> T {
+    value
+        .and_then(|v| v.parse().ok())
+        .unwrap_or_else(|| panic!("{} needs a positive number", flag))
+}
+
+fn main() {
+    let Args {
+        graph_path,
+        fps,
+        fixed_step,
+        frames,
+        png_dir,
+        ndi: send_ndi,
+    } = Args::parse();
+    let fps = fps.get();
+
+    if send_ndi {
+        ndi::initialize().unwrap();
+    }
+
+    let graph = GraphDescription::load(Path::new(&graph_path))
+        .unwrap_or_else(|e| panic!("failed to load graph {}: {}", graph_path, e));
+
+    // to use precompiled SPIRV(GLSL) shaders without decompilation(naga)
+    // and the texture formats used by the graph
+    let features = wgpu::Features::SPIRV_SHADER_PASSTHROUGH | graph.required_features();
+    let gpu = HeadlessDevice::new(features)
+        .expect("no Vulkan adapter supporting SPIR-V passthrough and the graph formats found");
+    println!(
+        "rendering on {} ({:?})",
+        gpu.info.name, gpu.info.device_type
+    );
+    let mut store = ParameterStore::new();
+    let mut registry = NodeRegistry::with_builtins();
+    let mut tree = graph
+        .build(&mut registry, &gpu.device, &mut store)
+        .unwrap_or_else(|e| panic!("failed to build graph {}: {}", graph_path, e));
+    let mut editor = GraphEditor::new(
+        registry,
+        graph.texture_size,
+        graph.format,
+        graph.stream.clone(),
+    );
+    let mut program = ProgramManager::new();
+    // with a fixed step every frame advances 1/fps, independent of the render time
+    let mut clock = if fixed_step {
+        Clock::with_fixed_fps(&mut store, fps as f32)
+    } else {
+        Clock::new(&mut store)
+    };
+    let mut output_stage = OutputStage::new(&gpu.device, graph.texture_size, &mut store)
+        .unwrap_or_else(|e| panic!("failed to create the output stage: {}", e));
+
+    println!("{}", store);
+
+    let host_info =
+        oscq_rs::OscHostInfo::new("visgen_graph".to_string(), "127.0.0.1".to_string(), PORT)
+            .with_ext_access()
+            .with_ext_value()
+            .with_ext_description();
+    let query = store.create_query(host_info);
+    oscq_rs::spawn_oscquery_service(query, "0.0.0.0:3030".parse().unwrap());
+    let receiver: osc::Receiver = osc::receiver(PORT).unwrap();
+
+    let texture_capturer = wgpu::TextureCapturer::default();
+    let mut ndi_stream =
+        send_ndi.then(|| NdiStream::new(graph.stream.name.clone(), graph.stream.framerate));
+    let mut outputs: Vec<NdiOutput> = if send_ndi {
+        graph
+            .outputs
+            .iter()
+            .map(|output| NdiOutput::new(output.clone(), &gpu.device, &mut store))
+            .collect::<Result<_, _>>()
+            .unwrap_or_else(|e| panic!("failed to create the outputs: {}", e))
+    } else {
+        Vec::new()
+    };
+    let mut file_output = png_dir.map(|dir| {
+        FileOutput::new(dir.clone())
+            .unwrap_or_else(|e| panic!("failed to create {}: {}", dir.display(), e))
+    });
+
+    let frame_duration = Duration::from_secs_f64(1.0 / fps as f64);
+    let start = Instant::now();
+    let mut last_frame = start;
+    let mut next_frame = start;
+    let mut frame = 0;
+
+    while Some(frame) != frames {
+        let now = Instant::now();
+
+        //OSC Receiving
+        for (packet, _) in receiver.try_iter() {
+            if let osc::Packet::Message(message) = packet {
+                store.update(&message);
+                program.update_osc(clock.time(), &store, &message);
+                editor.update_osc(&mut tree, &mut store, &gpu.device, &message);
+                clock.update_osc(&message);
+                println!("{:?}", message);
+            }
+        }
+
+        let output_size = tree
+            .node_info(tree.root())
+            .map_or(graph.texture_size, |info| info.size);
+        clock.tick(&store, now.duration_since(last_frame).as_secs_f32());
+        // programs are interpolated in show time, so they follow pause, speed and seek
+        program.update(clock.time(), &mut store);
+        let ctx = clock.context(&gpu.device, &gpu.queue, output_size);
+        tree.update(&ctx, &store);
+        output_stage.update(&ctx, &store, &tree);
+
+        let timecode = clock.timecode();
+        if let Some(stream) = ndi_stream.as_mut() {
+            if stream.frame_due() {
+                stream.update_snapshot(output_stage.snapshot(&ctx, &texture_capturer), timecode);
+            } else {
+                stream.send_video_from_queue();
+            }
+        }
+        if let Some(files) = file_output.as_mut() {
+            files.update_snapshot(output_stage.snapshot(&ctx, &texture_capturer));
+        }
+        for output in outputs.iter_mut() {
+            output.update(&tree, &ctx, &store, &texture_capturer, timecode);
+        }
+
+        // finish the snapshots of the previous frames
+        gpu.poll();
+
+        last_frame = now;
+        frame += 1;
+
+        // keep the framerate, if a frame took too long the next one starts right away
+        next_frame += frame_duration;
+        match next_frame.checked_duration_since(Instant::now()) {
+            Some(wait) => std::thread::sleep(wait),
+            None => next_frame = Instant::now(),
+        }
+    }
+
+    // Wait for capture to finish.
+    texture_capturer
+        .await_active_snapshots(&gpu.device)
+        .expect("Failed closing snapshots");
+    if let Some(stream) = ndi_stream.as_mut() {
+        stream.send_video_from_queue();
+    }
+    for output in outputs.iter_mut() {
+        output.send_video_from_queue();
+    }
+    println!("Done!");
+
+    if send_ndi {
+        unsafe {
+            ndi::cleanup();
+        }
+    }
+}
@@ -0,0 +1,229 @@
+use nannou::wgpu::{
+    CommandEncoderDescriptor, Device, Queue, TextueSnapshot, Texture, TextureBuilder,
+    TextureCapturer, TextureUsages, TextureView,
+};
+use nannou::Frame;
+use serde::{Deserialize, Serialize};
+
+use crate::RenderContext;
+
+/// texture that is used if an optional input is not connected
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Fallback {
+    Black,
+    White,
+}
+
+/// how texture coordinates outside of (0, 1) are sampled
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SamplerAddress {
+    /// the border pixels are repeated
+    #[default]
+    Clamp,
+    /// the texture is tiled
+    Repeat,
+    /// the texture is tiled, every other tile is mirrored
+    Mirror,
+    /// transparent black outside of the texture
+    ///
+    /// needs [nannou::wgpu::Features::ADDRESS_MODE_CLAMP_TO_BORDER]
+    Border,
+}
+
+/// how texels are interpolated
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SamplerFilter {
+    Nearest,
+    #[default]
+    Linear,
+}
+
+///
+/// How a node samples the texture of an [InputPort]
+///
+/// the default clamps and filters linearly, without mipmaps.
+/// with `mipmaps` the [crate::TextureTree] copies the input into a texture with a full mip chain,
+/// so inputs that are scaled down, e.g. by tiling or a zoom out, do not alias.
+///
+/// ```json
+/// "samplers": { "input": { "address": "repeat", "filter": "nearest", "mipmaps": true } }
+/// ```
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct SamplerConfig {
+    pub address: SamplerAddress,
+    pub filter: SamplerFilter,
+    pub mipmaps: bool,
+}
+
+impl SamplerConfig {
+    /// descriptor of a sampler with this configuration
+    pub fn descriptor(&self) -> nannou::wgpu::SamplerDescriptor<'static> {
+        let address = match self.address {
+            SamplerAddress::Clamp => nannou::wgpu::AddressMode::ClampToEdge,
+            SamplerAddress::Repeat => nannou::wgpu::AddressMode::Repeat,
+            SamplerAddress::Mirror => nannou::wgpu::AddressMode::MirrorRepeat,
+            SamplerAddress::Border => nannou::wgpu::AddressMode::ClampToBorder,
+        };
+        let filter = match self.filter {
+            SamplerFilter::Nearest => nannou::wgpu::FilterMode::Nearest,
+            SamplerFilter::Linear => nannou::wgpu::FilterMode::Linear,
+        };
+        let mut descriptor = nannou::wgpu::SamplerBuilder::new()
+            .address_mode(address)
+            .mag_filter(filter)
+            .min_filter(filter)
+            .mipmap_filter(filter)
+            .into_descriptor();
+        if self.address == SamplerAddress::Border {
+            descriptor.border_color = Some(nannou::wgpu::SamplerBorderColor::TransparentBlack);
+        }
+        descriptor
+    }
+}
+
+///
+/// Describes an input of a [crate::TextureNode]
+///
+/// The inputs of a node are connected to its ports in order.
+/// A port takes `count` inputs, only the last port of a node should take more than one.
+#[derive(Clone, Debug, PartialEq)]
+pub struct InputPort {
+    /// name of the port, used in error messages
+    pub name: String,
+    /// a required port must be connected, otherwise the graph is invalid
+    pub required: bool,
+    /// number of inputs this port accepts
+    pub count: usize,
+    /// texture used for an unconnected optional port
+    pub fallback: Fallback,
+    /// default sampling of the input, it can be changed in the graph, see [SamplerConfig]
+    pub sampler: SamplerConfig,
+}
+
+impl InputPort {
+    /// port that must be connected
+    pub fn required(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            required: true,
+            count: 1,
+            fallback: Fallback::Black,
+            sampler: SamplerConfig::default(),
+        }
+    }
+
+    /// port that uses a `fallback` texture if not connected
+    pub fn optional(name: &str, fallback: Fallback) -> Self {
+        Self {
+            name: name.to_string(),
+            required: false,
+            count: 1,
+            fallback,
+            sampler: SamplerConfig::default(),
+        }
+    }
+
+    /// let the port accept multiple inputs
+    pub fn count(mut self, count: usize) -> Self {
+        self.count = count;
+        self
+    }
+
+    /// change the default sampling of the input
+    pub fn sampler(mut self, sampler: SamplerConfig) -> Self {
+        self.sampler = sampler;
+        self
+    }
+}
+
+///
+/// Black and white textures used for unconnected optional [InputPort]s
+///
+pub struct FallbackTextures {
+    black: Texture,
+    white: Texture,
+    black_view: TextureView,
+    white_view: TextureView,
+}
+
+impl FallbackTextures {
+    /// create and clear the fallback textures
+    pub fn new(device: &Device, queue: &Queue) -> Self {
+        let black = Self::solid_texture(device, queue, nannou::wgpu::Color::BLACK);
+        let white = Self::solid_texture(device, queue, nannou::wgpu::Color::WHITE);
+        let black_view = black.view().build();
+        let white_view = white.view().build();
+        Self {
+            black,
+            white,
+            black_view,
+            white_view,
+        }
+    }
+
+    /// view of a fallback texture
+    pub fn texture_view(&self, fallback: Fallback) -> TextureView {
+        match fallback {
+            Fallback::Black => self.black_view.clone(),
+            Fallback::White => self.white_view.clone(),
+        }
+    }
+
+    /// a fallback texture
+    pub fn texture(&self, fallback: Fallback) -> &Texture {
+        match fallback {
+            Fallback::Black => &self.black,
+            Fallback::White => &self.white,
+        }
+    }
+
+    /// capture a fallback texture, e.g. to send it over NDI
+    pub fn snapshot(
+        &self,
+        ctx: &RenderContext,
+        texture_capturer: &TextureCapturer,
+        fallback: Fallback,
+    ) -> TextueSnapshot {
+        let device = ctx.device;
+        let ce_desc = CommandEncoderDescriptor {
+            label: Some("texture capture"),
+        };
+        let mut encoder = device.create_command_encoder(&ce_desc);
+
+        let snapshot = texture_capturer.capture(device, &mut encoder, self.texture(fallback));
+
+        ctx.queue.submit(Some(encoder.finish()));
+
+        snapshot
+    }
+
+    /// 1x1 texture cleared to a single color, inputs are sampled using uv coordinates
+    fn solid_texture(device: &Device, queue: &Queue, color: nannou::wgpu::Color) -> Texture {
+        let texture = TextureBuilder::new()
+            .size([1, 1])
+            .usage(
+                TextureUsages::RENDER_ATTACHMENT
+                    | TextureUsages::COPY_SRC
+                    | TextureUsages::TEXTURE_BINDING,
+            )
+            .format(Frame::TEXTURE_FORMAT)
+            .build(device);
+
+        let ce_desc = CommandEncoderDescriptor {
+            label: Some("fallback texture"),
+        };
+        let mut encoder = device.create_command_encoder(&ce_desc);
+        {
+            let view = texture.view().build();
+            let _render_pass = nannou::wgpu::RenderPassBuilder::new()
+                .color_attachment(&view, |c| c.load_op(nannou::wgpu::LoadOp::Clear(color)))
+                .begin(&mut encoder);
+        }
+        queue.submit(Some(encoder.finish()));
+
+        texture
+    }
+}
@@ -1,18 +1,45 @@
-mod fragment_node;
+mod clock;
+mod graph_description;
+mod graph_dot;
+mod graph_editor;
+mod group_node;
+mod input_port;
+mod monitor;
+mod node_controls;
+mod node_format;
+mod node_registry;
 mod osc_convert;
+mod output_stage;
 mod parameter;
+mod phase;
+mod render_context;
 pub mod shader_target;
 pub mod shapes;
+mod stats;
 mod texture_node;
 mod texture_target;
 mod texture_tree;
+mod uniform_layout;
 
-pub use fragment_node::*;
+pub use clock::*;
+pub use graph_description::*;
+pub use graph_editor::*;
+pub use group_node::*;
+pub use input_port::*;
+pub use monitor::*;
+pub use node_controls::*;
+pub use node_format::*;
+pub use node_registry::*;
+pub use output_stage::*;
 pub use parameter::*;
+pub use phase::*;
+pub use render_context::*;
 pub use shapes::Vertex2D;
+pub use stats::*;
 pub use texture_node::*;
 pub use texture_target::*;
 pub use texture_tree::*;
+pub use uniform_layout::*;
 
 ///
 /// Texture generators
@@ -30,15 +57,20 @@ pub mod generators {
 pub mod combiner {
     pub mod fader_node;
     pub mod masking_node;
-    pub mod shader_combiner;
 }
 
 pub mod effects {
     pub mod color_ramp;
+    pub mod feedback;
 }
 
 pub mod util {
+    pub mod file_output;
+    pub mod headless_device;
+    pub mod mipmaps;
+    pub mod ndi_output;
     pub mod ndi_stream;
+    pub mod resample;
     pub mod shader;
 }
 
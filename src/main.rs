@@ -1,27 +1,31 @@
-use indextree::Arena;
 use nannou::prelude::*;
 use nannou_osc as osc;
+use std::path::Path;
 
-use visgen_graph::combiner::fader_node::FaderNode;
-use visgen_graph::combiner::masking_node::MaskingNode;
-use visgen_graph::effects::color_ramp::ColorRampNode;
-use visgen_graph::generators::circles::CircleGenerator;
-// use visgen_graph::generators::clouds::CloudsNode;
-use visgen_graph::generators::perlin::PerlinTextureNode;
-use visgen_graph::generators::stripes::StripeGenerator;
-use visgen_graph::generators::wave::WaveTextureNode;
 use visgen_graph::program::program::ProgramManager;
-use visgen_graph::{ParameterStore, TextureModelNode, TextureNode, TextureTree};
+use visgen_graph::util::ndi_output::NdiOutput;
+use visgen_graph::util::ndi_stream::NdiStream;
+use visgen_graph::{
+    Clock, GraphDescription, GraphEditor, Monitor, NodeRegistry, OutputStage, ParameterStore,
+    TextureTree,
+};
 
 pub const DEFAULT_POWER_PREFERENCE: wgpu::PowerPreference = wgpu::PowerPreference::HighPerformance;
 
 struct Model {
     receiver: osc::Receiver,
+    stats_sender: Option<osc::Sender<osc::Connected>>,
+    last_stats: f32,
     tree: TextureTree,
+    clock: Clock,
+    editor: GraphEditor,
+    monitor: Monitor,
+    output_stage: OutputStage,
     store: ParameterStore,
     program: ProgramManager,
     texture_capturer: wgpu::TextureCapturer,
-    ndi_stream: visgen_graph::util::ndi_stream::NdiStream,
+    ndi_stream: NdiStream,
+    outputs: Vec<NdiOutput>,
     lasttime: f32,
 }
 
@@ -40,20 +44,81 @@ fn main() {
 }
 
 const PORT: u16 = 6060;
+const DEFAULT_GRAPH: &str = "graphs/default.json";
+const PREVIEW_FLAG: &str = "--preview";
+const STATS_FLAG: &str = "--stats";
+const GPU_TIMING_FLAG: &str = "--gpu-timing";
+/// seconds between two stats messages
+const STATS_INTERVAL: f32 = 1.0;
+
+/// command line arguments
+struct Args {
+    graph_path: String,
+    preview: bool,
+    stats_target: Option<String>,
+    gpu_timing: bool,
+}
+
+impl Args {
+    fn parse() -> Self {
+        let mut args = Args {
+            graph_path: DEFAULT_GRAPH.to_string(),
+            preview: false,
+            stats_target: None,
+            gpu_timing: false,
+        };
+        let mut graph_path = None;
+        let mut iter = std::env::args().skip(1);
+        while let Some(arg) = iter.next() {
+            match arg.as_str() {
+                PREVIEW_FLAG => args.preview = true,
+                GPU_TIMING_FLAG => args.gpu_timing = true,
+                STATS_FLAG => args.stats_target = iter.next(),
+                _ if graph_path.is_none() => graph_path = Some(arg),
+                _ => println!("unknown argument {}", arg),
+            }
+        }
+        if let Some(path) = graph_path {
+            args.graph_path = path;
+        }
+        args
+    }
+}
 
 fn model(app: &App) -> Model {
-    let texture_size = [512, 512];
+    // graph file can be given as first argument
+    // `--preview` adds a second NDI stream sending the monitored node
+    // `--stats <host:port>` sends the timing stats of the tree to an OSC client
+    // `--gpu-timing` measures the GPU time of the nodes, the adapter must support timestamp queries
+    let Args {
+        graph_path,
+        preview,
+        stats_target,
+        gpu_timing,
+    } = Args::parse();
+    let graph = GraphDescription::load(Path::new(&graph_path))
+        .unwrap_or_else(|e| panic!("failed to load graph {}: {}", graph_path, e));
+    let texture_size = graph.texture_size;
     let mut store = ParameterStore::new();
 
     // to use precompiled SPIRV(GLSL) shaders without decompilation(naga)
+    // and the texture formats used by the graph
+    let mut features = wgpu::Features::SPIRV_SHADER_PASSTHROUGH | graph.required_features();
+    if gpu_timing {
+        features |= wgpu::Features::TIMESTAMP_QUERY;
+    }
     let device_desc = wgpu::DeviceDescriptor {
         label: Some("SPIRV SHADERS"),
-        features: wgpu::Features::SPIRV_SHADER_PASSTHROUGH,
+        features,
         limits: wgpu::Limits::default(),
     };
 
-    // Create the window.
-    let [win_w, win_h] = [texture_size[0], texture_size[1]];
+    // Create the window, with the size of the root node
+    let [win_w, win_h] = graph
+        .nodes
+        .iter()
+        .find(|node| node.name == graph.root)
+        .map_or(texture_size, |node| node.output_size(texture_size));
     let w_id = app
         .new_window()
         .size(win_w, win_h)
@@ -64,8 +129,14 @@ fn model(app: &App) -> Model {
         .build()
         .unwrap();
     let window = app.window(w_id).unwrap();
-    //let tree = build_tree(&window, texture_size, &mut store);
-    let tree = build_tree(&window, texture_size, &mut store);
+    let mut registry = NodeRegistry::with_builtins();
+    let tree = graph
+        .build(&mut registry, window.device(), &mut store)
+        .unwrap_or_else(|e| panic!("failed to build graph {}: {}", graph_path, e));
+    let editor = GraphEditor::new(registry, texture_size, graph.format, graph.stream.clone());
+    let clock = Clock::new(&mut store);
+    let output_stage = OutputStage::new(window.device(), [win_w, win_h], &mut store)
+        .unwrap_or_else(|e| panic!("failed to create the output stage: {}", e));
 
     println!("{}", store);
 
@@ -79,33 +150,63 @@ fn model(app: &App) -> Model {
     oscq_rs::spawn_oscquery_service(query, "0.0.0.0:3030".parse().unwrap());
 
     let receiver: osc::Receiver = osc::receiver(PORT).unwrap();
+    let stats_sender = stats_target.map(|target| {
+        osc::sender()
+            .unwrap()
+            .connect(&target)
+            .unwrap_or_else(|e| panic!("failed to connect stats to {}: {}", target, e))
+    });
     let program = ProgramManager::new();
 
     let texture_capturer = wgpu::TextureCapturer::default();
-    let ndi_stream = visgen_graph::util::ndi_stream::NdiStream::new("visgen_graph".to_string(), 60);
+    let ndi_stream = NdiStream::new(graph.stream.name.clone(), graph.stream.framerate);
+    let outputs = graph
+        .outputs
+        .iter()
+        .map(|output| NdiOutput::new(output.clone(), window.device(), &mut store))
+        .collect::<Result<_, _>>()
+        .unwrap_or_else(|e| panic!("failed to create the outputs: {}", e));
+    let preview = preview.then(|| NdiStream::new("visgen_graph_preview".to_string(), 60));
+    let monitor = Monitor::new(window.device(), &mut store, preview)
+        .unwrap_or_else(|e| panic!("failed to create the monitor: {}", e));
 
     Model {
         receiver,
+        stats_sender,
+        last_stats: 0f32,
         tree,
+        clock,
+        editor,
+        monitor,
+        output_stage,
         store,
         program,
         texture_capturer,
         ndi_stream,
+        outputs,
         lasttime: 0f32,
     }
 }
 
-fn update(app: &App, model: &mut Model, _update: Update) {
+fn update(app: &App, model: &mut Model, update: Update) {
+    let win = app.main_window();
+
     //OSC Receiving
     for (packet, _) in model.receiver.try_iter() {
         if let osc::Packet::Message(message) = packet {
             model.store.update(&message);
-            model.program.update_osc(app.time, &model.store, &message);
+            model
+                .program
+                .update_osc(model.clock.time(), &model.store, &message);
+            model
+                .editor
+                .update_osc(&mut model.tree, &mut model.store, win.device(), &message);
+            model.monitor.update_osc(&mut model.tree, &message);
+            model.clock.update_osc(&message);
             println!("{:?}", message);
         }
     }
 
-    model.program.update(app.time, &mut model.store);
     /*println!(
         "runtime {} timing {} fps {} ",
         app.time,
@@ -113,132 +214,80 @@ fn update(app: &App, model: &mut Model, _update: Update) {
         1.0f32 / (app.time - model.lasttime)
     );*/
     model.lasttime = app.time;
+
+    // nodes added without info have the size of the window
+    let (win_w, win_h) = win.inner_size_pixels();
+    let output_size = model
+        .tree
+        .node_info(model.tree.root())
+        .map_or([win_w, win_h], |info| info.size);
+    model
+        .clock
+        .tick(&model.store, update.since_last.as_secs_f32());
+    // programs are interpolated in show time, so they follow pause, speed and seek
+    model.program.update(model.clock.time(), &mut model.store);
+    let ctx = model.clock.context(win.device(), win.queue(), output_size);
+
     // Update the Model Tree
-    let win = app.main_window();
-    model.tree.update(app, &win, &model.store);
+    model.tree.update(&ctx, &model.store);
+    // color transform and conversion to 8 bit for the window and NDI
+    model.output_stage.update(&ctx, &model.store, &model.tree);
+
+    // the render times are sent in real time, also while the clock is paused
+    if let Some(sender) = model.stats_sender.as_ref() {
+        if app.time - model.last_stats >= STATS_INTERVAL {
+            for message in model.tree.stats().messages() {
+                sender.send(osc::Packet::Message(message)).ok();
+            }
+            model.last_stats = app.time;
+        }
+    }
 
-    let snapshot = model.tree.snapshot(&win, &model.texture_capturer);
-    let timecode = (app.time * 10000f32) as i64;
+    let timecode = model.clock.timecode();
 
     // send the last queued image in the stream, and queue the next snapshot
     // this is slow but for now this works 20-30fps
-    model.ndi_stream.update_snapshot(snapshot, timecode);
+    if model.ndi_stream.frame_due() {
+        let snapshot = model.output_stage.snapshot(&ctx, &model.texture_capturer);
+        model.ndi_stream.update_snapshot(snapshot, timecode);
+    } else {
+        model.ndi_stream.send_video_from_queue();
+    }
+    model.monitor.update(
+        &model.tree,
+        &ctx,
+        &model.store,
+        &model.texture_capturer,
+        timecode,
+    );
+
+    for output in model.outputs.iter_mut() {
+        output.update(
+            &model.tree,
+            &ctx,
+            &model.store,
+            &model.texture_capturer,
+            timecode,
+        );
+    }
 }
 
 fn view(app: &App, model: &Model, frame: Frame) {
     let draw = app.draw();
     frame.clear(BLACK);
 
-    draw.texture(&model.tree.output());
+    // the window shows the monitored node, the program output is sent over NDI
+    let texture = match model.monitor.selected(&model.tree) {
+        Some(_) => model.monitor.output(),
+        None => model.output_stage.output(),
+    };
+    draw.texture(&texture);
 
     //draw.ellipse().x_y(0.1, 0.1).radius(5.0).color(RED); // test primitive
 
     draw.to_frame(app, &frame).unwrap();
 }
 
-fn build_tree(win: &Window, size: [u32; 2], store: &mut ParameterStore) -> TextureTree {
-    // Create a new arena
-
-    let device = win.device();
-
-    let mut arena: Arena<Box<dyn TextureNode>> = Arena::new();
-
-    // Add some new nodes to the arena
-    let stripe1 = StripeGenerator::new("Stripes1".to_string(), size, store);
-    let s1 = arena.new_node(Box::new(TextureModelNode::new(stripe1, device, size)));
-
-    let stripe2 = StripeGenerator::new("Stripes2".to_string(), size, store);
-    let s2 = arena.new_node(Box::new(TextureModelNode::new(stripe2, device, size)));
-
-    let w1 = arena.new_node(Box::new(WaveTextureNode::new(
-        "wave1".to_string(),
-        size,
-        store,
-        device,
-    )));
-
-    let w2 = arena.new_node(Box::new(WaveTextureNode::new(
-        "wave2".to_string(),
-        size,
-        store,
-        device,
-    )));
-
-    let circles = CircleGenerator::new("circles".to_string(), size, store);
-    let c1 = arena.new_node(Box::new(TextureModelNode::new(circles, device, size)));
-
-    let m1 = arena.new_node(Box::new(MaskingNode::new(
-        "mask".to_string(),
-        size,
-        store,
-        device,
-    )));
-
-    let f1 = arena.new_node(Box::new(FaderNode::new(
-        "fader1".to_string(),
-        size,
-        store,
-        device,
-    )));
-    let f2 = arena.new_node(Box::new(FaderNode::new(
-        "fader2".to_string(),
-        size,
-        store,
-        device,
-    )));
-
-    /* let b = arena.new_node(Box::new(NodeModel::new(20, device, size)));
-    let c = arena.new_node(Box::new(NodeModel::new(30, device, size)));
-    let d = arena.new_node(Box::new(NodeModel::new(40, device, size)));
-    let e = arena.new_node(Box::new(NodeModel::new(50, device, size)));
-    let f = arena.new_node(Box::new(NodeModel::new(60, device, size)));
-    let g = arena.new_node(Box::new(NodeModel::new(70, device, size)));
-    let h = arena.new_node(Box::new(NodeModel::new(80, device, size)));*/
-
-    // Build tree
-    //           f1
-    //         m1     f2
-    //      c1 w2 s1    w1 s2
-
-    f1.append(m1, &mut arena);
-    f1.append(f2, &mut arena);
-
-    m1.append(c1, &mut arena);
-    m1.append(w2, &mut arena);
-    m1.append(s1, &mut arena);
-
-    f2.append(w1, &mut arena);
-    f2.append(s2, &mut arena);
-
-    //d.append(b, &mut arena);
-    //d.append(c, &mut arena);
-    //d.append(a, &mut arena); // stripes as mask
-
-    TextureTree::new(arena, f1)
-}
-
-fn build_tree_single(win: &Window, size: [u32; 2], store: &mut ParameterStore) -> TextureTree {
-    let device = win.device();
-    let mut arena: Arena<Box<dyn TextureNode>> = Arena::new();
-
-    let g1 = arena.new_node(Box::new(PerlinTextureNode::new(
-        "clouds".to_string(),
-        size,
-        store,
-        device,
-    )));
-
-    let e1 = arena.new_node(Box::new(ColorRampNode::new(
-        "ramp".to_string(),
-        size,
-        store,
-        device,
-    )));
-    e1.append(g1, &mut arena);
-
-    TextureTree::new(arena, e1)
-}
-
 // ToDo FixMe Rust Panics when the programs stops
 // maybe texture capture still copying
 fn window_exit(a: &App, m: &mut Model) {
@@ -253,6 +302,10 @@ fn window_exit(a: &App, m: &mut Model) {
 // Wait for capture to finish.
 fn exit(_app: &App, mut model: Model) {
     model.ndi_stream.send_video_from_queue();
+    model.monitor.send_video_from_queue();
+    for output in model.outputs.iter_mut() {
+        output.send_video_from_queue();
+    }
     // wait for NDI to finish
     println!("Done!");
 }
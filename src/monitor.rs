@@ -0,0 +1,130 @@
+use crate::util::ndi_stream::NdiStream;
+use crate::{OutputStage, ParameterStore, RenderContext, TextureTree, UniformLayoutError};
+use indextree::NodeId;
+use nannou::wgpu::{Device, TextureCapturer, TextureView};
+use nannou_osc::Message;
+
+///
+/// Shows the output of any node of a [TextureTree] without changing the program output
+///
+/// # OSC Commands
+///
+/// | Address           | Arguments       | Description                                        |
+/// |-------------------|-----------------|----------------------------------------------------|
+/// | `/monitor/select` | `name: String`  | monitor a node, an empty name monitors the output  |
+///
+/// The monitored node is rendered like an output, see [TextureTree::set_monitored].
+/// It is converted by its own [OutputStage], using the `/output/color/*` parameters of the program output,
+/// and can also be sent over an optional preview [NdiStream].
+pub struct Monitor {
+    selected: Option<String>,
+    preview: Option<NdiStream>,
+    stage: OutputStage,
+}
+
+impl Monitor {
+    /// create a new monitor showing the output of the tree
+    /// - `preview`: stream that sends the monitored node
+    pub fn new(
+        device: &Device,
+        store: &mut ParameterStore,
+        preview: Option<NdiStream>,
+    ) -> Result<Self, UniformLayoutError> {
+        Ok(Self {
+            selected: None,
+            preview,
+            stage: OutputStage::new(device, [1, 1], store)?,
+        })
+    }
+
+    /// select the monitored node using osc messages
+    pub fn update_osc(&mut self, tree: &mut TextureTree, msg: &Message) {
+        if msg.addr != "/monitor/select" {
+            return;
+        }
+
+        let name = msg
+            .args
+            .as_ref()
+            .and_then(|args| args.first())
+            .and_then(|arg| arg.clone().string())
+            .unwrap_or_default();
+
+        let node = if name.is_empty() {
+            None
+        } else {
+            match tree.find(&name) {
+                Some(node) => Some(node),
+                None => {
+                    println!("monitor_error: unknown node '{}'", name);
+                    return;
+                }
+            }
+        };
+
+        match tree.set_monitored(node) {
+            Ok(()) => self.selected = node.map(|_| name),
+            Err(e) => println!("monitor_error: {}", e),
+        }
+    }
+
+    /// name of the monitored node, [None] if the output is monitored
+    pub fn selected(&self, tree: &TextureTree) -> Option<&str> {
+        self.node(tree).and(self.selected.as_deref())
+    }
+
+    /// converted texture of the monitored node, updated by [Monitor::update]
+    pub fn output(&self) -> TextureView {
+        self.stage.output()
+    }
+
+    /// convert the monitored node and send it over the preview stream, if there is one
+    ///
+    /// without a selection the output of the tree is only converted for the preview.
+    /// must be called after the tree was updated
+    pub fn update(
+        &mut self,
+        tree: &TextureTree,
+        ctx: &RenderContext,
+        store: &ParameterStore,
+        texture_capturer: &TextureCapturer,
+        timecode: i64,
+    ) {
+        let node = self.node(tree);
+        let send = match self.preview.as_mut() {
+            Some(preview) => {
+                let due = preview.frame_due();
+                if !due {
+                    preview.send_video_from_queue();
+                }
+                due
+            }
+            None => false,
+        };
+        if node.is_none() && !send {
+            return;
+        }
+
+        let texture = match node {
+            Some(node) => tree.node_output(node),
+            None => tree.output(),
+        };
+        let size = texture.size();
+        self.stage.convert(ctx, store, texture, size);
+        if let Some(preview) = self.preview.as_mut().filter(|_| send) {
+            preview.update_snapshot(self.stage.snapshot(ctx, texture_capturer), timecode);
+        }
+    }
+
+    /// send the last queued frame of the preview stream
+    pub fn send_video_from_queue(&mut self) {
+        if let Some(preview) = self.preview.as_mut() {
+            preview.send_video_from_queue();
+        }
+    }
+
+    /// the monitored node, nodes removed from the tree are no longer monitored
+    fn node(&self, tree: &TextureTree) -> Option<NodeId> {
+        tree.monitored()
+    }
+}
@@ -0,0 +1,59 @@
+use crate::{ParameterEnd, ParameterEndpoint, ParameterFactory, ParameterStore};
+
+/// how a node is handled by the [crate::TextureTree] in the current frame
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum NodeState {
+    /// the node is rendered
+    Active,
+    /// the node is not rendered, its first input is used as output
+    Bypass,
+    /// the node is not rendered, its output is black
+    Mute,
+}
+
+///
+/// Parameters every node of a [crate::TextureTree] gets, next to its own parameters
+///
+/// # OSC Parameters used
+///
+/// | Endpoint    | Description                                  |  Datatype    |
+/// |-------------|----------------------------------------------|--------------|
+/// | `./bypass`  | pass the first input through, black if none  | `bool`       |
+/// | `./mute`    | output black and skip rendering              | `bool`       |
+/// | `./solo`    | use this node as output of the tree          | `bool`       |
+///
+/// mute wins over bypass.
+pub struct NodeControls {
+    bypass: ParameterEndpoint<bool>,
+    mute: ParameterEndpoint<bool>,
+    solo: ParameterEndpoint<bool>,
+}
+
+impl NodeControls {
+    /// register the controls of the node `name`
+    pub fn new(name: String, store: &mut ParameterStore) -> Self {
+        let mut factory = ParameterFactory::new(name, store);
+
+        Self {
+            bypass: factory.build_default(false, "bypass".to_string()),
+            mute: factory.build_default(false, "mute".to_string()),
+            solo: factory.build_default(false, "solo".to_string()),
+        }
+    }
+
+    /// current state of the node
+    pub fn state(&self, store: &ParameterStore) -> NodeState {
+        if self.mute.get(store) {
+            NodeState::Mute
+        } else if self.bypass.get(store) {
+            NodeState::Bypass
+        } else {
+            NodeState::Active
+        }
+    }
+
+    /// check if the node is routed to the output
+    pub fn solo(&self, store: &ParameterStore) -> bool {
+        self.solo.get(store)
+    }
+}
@@ -0,0 +1,49 @@
+use nannou::wgpu::{Features, TextureFormat};
+use serde::{Deserialize, Serialize};
+
+/// 8-bit format the output is converted to before it is shown or sent, see [crate::OutputStage]
+pub const OUTPUT_FORMAT: TextureFormat = TextureFormat::Rgba8UnormSrgb;
+
+///
+/// Format of the texture a node renders to
+///
+/// float formats keep values above 1, so sums like a fader with many terms
+/// do not clip or band before the output converts them to [OUTPUT_FORMAT].
+///
+/// | Name          | Format                                | Bits per channel |
+/// |---------------|---------------------------------------|------------------|
+/// | `rgba8`       | 8-bit sRGB, the default               | 8                |
+/// | `rgba16float` | half float                            | 16               |
+/// | `rgba32float` | float, needs a device with [Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES] | 32 |
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum NodeFormat {
+    #[default]
+    Rgba8,
+    Rgba16Float,
+    Rgba32Float,
+}
+
+impl NodeFormat {
+    /// the wgpu format of the textures
+    pub fn texture_format(self) -> TextureFormat {
+        match self {
+            NodeFormat::Rgba8 => OUTPUT_FORMAT,
+            NodeFormat::Rgba16Float => TextureFormat::Rgba16Float,
+            NodeFormat::Rgba32Float => TextureFormat::Rgba32Float,
+        }
+    }
+
+    /// device features needed to filter and blend the format
+    pub fn required_features(self) -> Features {
+        match self {
+            NodeFormat::Rgba32Float => Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES,
+            _ => Features::empty(),
+        }
+    }
+
+    /// check if the default value is used, the format is then not stored
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
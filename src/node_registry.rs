@@ -0,0 +1,237 @@
+use crate::combiner::fader_node::FaderNode;
+use crate::combiner::masking_node::MaskingNode;
+use crate::effects::color_ramp::ColorRampNode;
+use crate::effects::feedback::FeedbackNode;
+use crate::generators::circles::CircleGenerator;
+use crate::generators::clouds::CloudsNode;
+use crate::generators::perlin::PerlinTextureNode;
+use crate::generators::stripes::StripeGenerator;
+use crate::generators::wave::WaveTextureNode;
+use crate::{
+    GraphError, GroupDescription, GroupNode, NodeFormat, ParameterStore, TextureModelNode,
+    TextureNode, UniformLayoutError,
+};
+use nannou::wgpu::Device;
+use serde::de::DeserializeOwned;
+use std::collections::BTreeMap;
+
+/// free form options of a node, as given in the graph file
+pub type NodeOptions = serde_json::Map<String, serde_json::Value>;
+
+/// function that creates a [TextureNode] from a [BuildContext]
+pub type NodeFactory = Box<dyn Fn(BuildContext) -> Result<Box<dyn TextureNode>, GraphError>>;
+
+///
+/// Everything a [NodeFactory] needs to create a node
+///
+pub struct BuildContext<'a> {
+    /// name of the node, base of the OSC addresses
+    pub name: String,
+    /// size of the output texture
+    pub size: [u32; 2],
+    /// format of the output texture
+    pub format: NodeFormat,
+    /// global [ParameterStore] to register the parameters in
+    pub store: &'a mut ParameterStore,
+    /// render device
+    pub device: &'a Device,
+    /// per node options
+    pub options: &'a NodeOptions,
+}
+
+impl<'a> BuildContext<'a> {
+    /// read a typed option
+    ///
+    /// returns [None] if the option is not set
+    /// and an [GraphError::InvalidOption] if it has the wrong type
+    pub fn option<T: DeserializeOwned>(&self, key: &str) -> Result<Option<T>, GraphError> {
+        match self.options.get(key) {
+            Some(value) => serde_json::from_value(value.clone())
+                .map(Some)
+                .map_err(|e| GraphError::InvalidOption(self.name.clone(), key.to_string(), e)),
+            None => Ok(None),
+        }
+    }
+}
+
+///
+/// Maps type names like `"wave"` or `"fader"` to a [NodeFactory]
+///
+/// all built-in nodes are available using [NodeRegistry::with_builtins],
+/// other nodes can be added with [NodeRegistry::register]
+/// and subgraphs with [NodeRegistry::register_group]
+///
+/// ```ignore
+/// let mut registry = NodeRegistry::with_builtins();
+/// registry.register("my_node", |ctx| {
+///     let format = ctx.format.texture_format();
+///     Ok(Box::new(MyNode::new(ctx.name, ctx.size, ctx.store, ctx.device, format)))
+/// });
+/// ```
+pub struct NodeRegistry {
+    factories: BTreeMap<String, NodeFactory>,
+    groups: BTreeMap<String, GroupDescription>,
+}
+
+impl NodeRegistry {
+    /// creates an empty registry
+    pub fn new() -> Self {
+        Self {
+            factories: BTreeMap::new(),
+            groups: BTreeMap::new(),
+        }
+    }
+
+    /// creates a registry containing all built-in node types
+    ///
+    /// | Type          | Node                                             |
+    /// |---------------|--------------------------------------------------|
+    /// | `stripes`     | [StripeGenerator]                                |
+    /// | `circles`     | [CircleGenerator]                                |
+    /// | `wave`        | [WaveTextureNode]                                |
+    /// | `perlin`      | [PerlinTextureNode]                              |
+    /// | `clouds`      | [CloudsNode]                                     |
+    /// | `mask`        | [MaskingNode]                                    |
+    /// | `fader`       | [FaderNode]                                      |
+    /// | `color_ramp`  | [ColorRampNode]                                  |
+    /// | `feedback`    | [FeedbackNode]                                   |
+    pub fn with_builtins() -> Self {
+        let mut registry = Self::new();
+
+        registry.register("stripes", |ctx| {
+            let model = StripeGenerator::new(ctx.name, ctx.size, ctx.store);
+            let format = ctx.format.texture_format();
+            Ok(Box::new(TextureModelNode::new(
+                model, ctx.device, ctx.size, format,
+            )))
+        });
+        registry.register("circles", |ctx| {
+            let model = CircleGenerator::new(ctx.name, ctx.size, ctx.store);
+            let format = ctx.format.texture_format();
+            Ok(Box::new(TextureModelNode::new(
+                model, ctx.device, ctx.size, format,
+            )))
+        });
+        registry.register("wave", |ctx| {
+            let format = ctx.format.texture_format();
+            let name = ctx.name.clone();
+            let node = WaveTextureNode::new(ctx.name, ctx.size, ctx.store, ctx.device, format);
+            shader_node(name, node)
+        });
+        registry.register("perlin", |ctx| {
+            let format = ctx.format.texture_format();
+            let name = ctx.name.clone();
+            let node = PerlinTextureNode::new(ctx.name, ctx.size, ctx.store, ctx.device, format);
+            shader_node(name, node)
+        });
+        registry.register("clouds", |ctx| {
+            let format = ctx.format.texture_format();
+            let name = ctx.name.clone();
+            let node = CloudsNode::new(ctx.name, ctx.size, ctx.store, ctx.device, format);
+            shader_node(name, node)
+        });
+        registry.register("mask", |ctx| {
+            let format = ctx.format.texture_format();
+            let name = ctx.name.clone();
+            let node = MaskingNode::new(ctx.name, ctx.size, ctx.store, ctx.device, format);
+            shader_node(name, node)
+        });
+        registry.register("fader", |ctx| {
+            let format = ctx.format.texture_format();
+            let name = ctx.name.clone();
+            let node = FaderNode::new(ctx.name, ctx.size, ctx.store, ctx.device, format);
+            shader_node(name, node)
+        });
+        registry.register("color_ramp", |ctx| {
+            let format = ctx.format.texture_format();
+            let name = ctx.name.clone();
+            let node = ColorRampNode::new(ctx.name, ctx.size, ctx.store, ctx.device, format);
+            shader_node(name, node)
+        });
+        registry.register("feedback", |ctx| {
+            let format = ctx.format.texture_format();
+            let name = ctx.name.clone();
+            let node = FeedbackNode::new(ctx.name, ctx.size, ctx.store, ctx.device, format);
+            shader_node(name, node)
+        });
+
+        registry
+    }
+
+    /// add a node type, replaces existing types with the same name
+    pub fn register<F>(&mut self, node_type: &str, factory: F)
+    where
+        F: Fn(BuildContext) -> Result<Box<dyn TextureNode>, GraphError> + 'static,
+    {
+        self.factories
+            .insert(node_type.to_string(), Box::new(factory));
+    }
+
+    /// add a group type, instances are [GroupNode]s
+    ///
+    /// groups replace node types with the same name
+    pub fn register_group(&mut self, node_type: &str, group: GroupDescription) {
+        self.groups.insert(node_type.to_string(), group);
+    }
+
+    /// all registered groups
+    pub fn groups(&self) -> &BTreeMap<String, GroupDescription> {
+        &self.groups
+    }
+
+    /// check if a node type is known
+    pub fn contains(&self, node_type: &str) -> bool {
+        self.factories.contains_key(node_type) || self.groups.contains_key(node_type)
+    }
+
+    /// names of all known node types
+    pub fn types(&self) -> impl Iterator<Item = &str> {
+        self.factories
+            .keys()
+            .filter(move |k| !self.groups.contains_key(*k))
+            .chain(self.groups.keys())
+            .map(|k| k.as_str())
+    }
+
+    /// create a node of the given type
+    ///
+    /// fails with [GraphError::UnsupportedFormat] if the device lacks the features of the format
+    pub fn build(
+        &self,
+        node_type: &str,
+        ctx: BuildContext,
+    ) -> Result<Box<dyn TextureNode>, GraphError> {
+        if !ctx
+            .device
+            .features()
+            .contains(ctx.format.required_features())
+        {
+            return Err(GraphError::UnsupportedFormat(ctx.name, ctx.format));
+        }
+        if let Some(group) = self.groups.get(node_type) {
+            return Ok(Box::new(GroupNode::new(ctx, group, self)?));
+        }
+        let factory = self
+            .factories
+            .get(node_type)
+            .ok_or_else(|| GraphError::UnknownNodeType(node_type.to_string()))?;
+        factory(ctx)
+    }
+}
+
+/// box a built-in node, a [UniformLayoutError] of its shaders is a [GraphError::Shader] of the node
+fn shader_node<N: TextureNode + 'static>(
+    name: String,
+    node: Result<N, UniformLayoutError>,
+) -> Result<Box<dyn TextureNode>, GraphError> {
+    match node {
+        Ok(node) => Ok(Box::new(node)),
+        Err(e) => Err(GraphError::Shader(name, e)),
+    }
+}
+
+impl Default for NodeRegistry {
+    fn default() -> Self {
+        Self::with_builtins()
+    }
+}
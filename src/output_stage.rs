@@ -0,0 +1,189 @@
+use crate::shader_target::{Shader2DTarget, ShaderTargetBuilder};
+use crate::shapes::{FULL_SCREEN_QUAD, FULL_SCREEN_QUAD_INDEX};
+use crate::util::shader::read_shader_file;
+use crate::{
+    ParameterEnd, ParameterEndpoint, ParameterFactory, ParameterStore, RenderContext, TextureTree,
+    UniformLayoutError, OUTPUT_FORMAT,
+};
+use nannou::image::EncodableLayout;
+use nannou::wgpu::{
+    CommandEncoderDescriptor, Device, TextueSnapshot, TextureCapturer, TextureView,
+};
+use wgpu::ShaderModuleDescriptorSpirV;
+
+/// Uniform data of the color transform of the [OutputStage]
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct UniformsOutputColor {
+    exposure: f32,
+    gamma: f32,
+    tonemap: i32,
+    linear: i32,
+}
+crate::uniform_layout!(UniformsOutputColor {
+    exposure,
+    gamma,
+    tonemap,
+    linear
+});
+
+///
+/// Converts the output of a [TextureTree] to [OUTPUT_FORMAT] before it is shown or sent
+///
+/// the nodes can render to float textures, see [crate::NodeFormat],
+/// this is the only place where their values are reduced to 8 bits.
+/// the converted texture has the output size of the [RenderContext],
+/// it is recreated when the size changes.
+///
+/// every texture that is shown or sent goes through a stage, e.g. the additional outputs
+/// and the [crate::Monitor] convert single nodes with [OutputStage::convert].
+/// all stages share the same parameters, so the transform is the same for every output.
+///
+/// ```ignore
+/// tree.update(&ctx, &store);
+/// output.update(&ctx, &store, &tree);
+/// ndi_stream.update_snapshot(output.snapshot(&ctx, &texture_capturer), timecode);
+/// ```
+///
+/// # Color transform
+///
+/// the output node is treated as linear light, like the values the shaders read from and write
+/// to sRGB textures. with `linear` disabled its values are decoded from sRGB first.
+/// then the exposure is applied, the colors are tonemapped, clipped to (0, 1)
+/// and the gamma is applied. the output texture encodes the result as sRGB,
+/// so the default values keep the output of the tree unchanged.
+///
+/// # OSC Parameters used
+///
+/// | Endpoint                 | Description                                     |  Datatype  | Range      |
+/// |--------------------------|-------------------------------------------------|------------|------------|
+/// | `/output/color/linear`   | the nodes work in linear light, else in sRGB    | `bool`     |            |
+/// | `/output/color/exposure` | brightness in stops, 0 keeps the brightness     | `f32`      | (-8, 8)    |
+/// | `/output/color/tonemap`  | 0 clip, 1 Reinhard, 2 ACES                      | `i32`      | (0,1,2)    |
+/// | `/output/color/gamma`    | additional gamma, 1 keeps the sRGB curve        | `f32`      | (0.2, 5.0) |
+///
+/// ## shaders used
+/// - `shader/minimal2d.vert` shared simple vertex shader
+/// - `shader/output_color.frag` shader for this
+pub struct OutputStage {
+    target: Shader2DTarget<UniformsOutputColor>,
+    size: [u32; 2],
+    linear: ParameterEndpoint<bool>,
+    exposure: ParameterEndpoint<f32>,
+    tonemap: ParameterEndpoint<i32>,
+    gamma: ParameterEndpoint<f32>,
+}
+
+impl OutputStage {
+    /// create an output stage with the output resolution `size` and register its parameters
+    ///
+    /// the parameters are only registered once, further stages use the same parameters
+    pub fn new(
+        device: &Device,
+        size: [u32; 2],
+        store: &mut ParameterStore,
+    ) -> Result<Self, UniformLayoutError> {
+        let target = build_target(device, size)?;
+        let mut factory = ParameterFactory::new("output/color".to_string(), store);
+
+        Ok(Self {
+            target,
+            size,
+            linear: factory.build_default(true, "linear".to_string()),
+            exposure: factory.build_default(0.0, "exposure".to_string()),
+            tonemap: factory.build_default(0, "tonemap".to_string()),
+            gamma: factory.build_default(1.0, "gamma".to_string()),
+        })
+    }
+
+    /// convert the output of the `tree`, must be called after the tree was updated
+    pub fn update(&mut self, ctx: &RenderContext, store: &ParameterStore, tree: &TextureTree) {
+        self.convert(ctx, store, tree.output(), ctx.output_size);
+    }
+
+    /// convert any texture, e.g. the output of a single node, scaled to `size`
+    ///
+    /// the previous size is kept if the target can not be recreated
+    pub fn convert(
+        &mut self,
+        ctx: &RenderContext,
+        store: &ParameterStore,
+        input: TextureView,
+        size: [u32; 2],
+    ) {
+        if size != self.size {
+            match build_target(ctx.device, size) {
+                Ok(target) => {
+                    self.target = target;
+                    self.size = size;
+                }
+                Err(e) => println!("OutputStage: {}", e),
+            }
+        }
+
+        let uniform = UniformsOutputColor {
+            exposure: self.exposure.get(store),
+            gamma: self.gamma.get(store),
+            tonemap: self.tonemap.get(store),
+            linear: self.linear.get(store) as i32,
+        };
+
+        let ce_desc = CommandEncoderDescriptor {
+            label: Some("output stage"),
+        };
+        let mut encoder = ctx.device.create_command_encoder(&ce_desc);
+        self.target.set_uniforms(ctx.queue, uniform);
+        self.target
+            .render_pass(ctx.device, &mut encoder, vec![input]);
+        ctx.queue.submit(Some(encoder.finish()));
+    }
+
+    /// the converted output
+    pub fn output(&self) -> TextureView {
+        self.target.texture_view()
+    }
+
+    /// create a snapshot of the converted output
+    pub fn snapshot(
+        &self,
+        ctx: &RenderContext,
+        texture_capturer: &TextureCapturer,
+    ) -> TextueSnapshot {
+        self.target.snapshot(ctx, texture_capturer)
+    }
+}
+
+fn build_target(
+    device: &Device,
+    size: [u32; 2],
+) -> Result<Shader2DTarget<UniformsOutputColor>, UniformLayoutError> {
+    let vert_raw = read_shader_file("shader/minimal2d_vert.spv");
+    let frag_raw = read_shader_file("shader/output_color_frag.spv");
+
+    let vert = ShaderModuleDescriptorSpirV {
+        label: Some("minimal2d_vert"),
+        source: nannou::wgpu::util::make_spirv_raw(vert_raw.as_bytes()),
+    };
+    let frag = ShaderModuleDescriptorSpirV {
+        label: Some("output_color_frag"),
+        source: nannou::wgpu::util::make_spirv_raw(frag_raw.as_bytes()),
+    };
+
+    let uniform = UniformsOutputColor {
+        exposure: 0.0,
+        gamma: 1.0,
+        tonemap: 0,
+        linear: 1,
+    };
+
+    ShaderTargetBuilder::new(
+        &vert,
+        &frag,
+        &FULL_SCREEN_QUAD,
+        &FULL_SCREEN_QUAD_INDEX,
+        uniform,
+    )
+    .input_textures(1)
+    .format(OUTPUT_FORMAT)
+    .build(device, size)
+}
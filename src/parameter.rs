@@ -138,10 +138,17 @@ type ParameterIndex = usize;
 /// Paths are an [BTreeMap], this maps Paths to an Index into the Vec.
 ///
 /// [Parameter]s get added when creating new [ParameterEndpoint]s or using the [ParameterFactory]
+///
+/// Every change increases the [ParameterStore::revision] of the store,
+/// [ParameterStore::changed_since] tells if parameters changed after a revision.
 #[derive(Serialize, Deserialize, Default, Debug)]
 pub struct ParameterStore {
     parameters: Vec<Parameter>,
     paths: BTreeMap<String, ParameterIndex>,
+    #[serde(skip)]
+    revisions: Vec<u64>,
+    #[serde(skip)]
+    revision: u64,
 }
 
 impl ParameterStore {
@@ -150,6 +157,8 @@ impl ParameterStore {
         Self {
             parameters: Vec::new(),
             paths: BTreeMap::new(),
+            revisions: Vec::new(),
+            revision: 0,
         }
     }
 
@@ -166,6 +175,7 @@ impl ParameterStore {
             let index = self.parameters.len();
             self.paths.insert(par.address.clone(), index);
             self.parameters.push(par);
+            self.touch(index);
             index
         }
     }
@@ -192,24 +202,101 @@ impl ParameterStore {
     /// and updates using the attached Values
     pub fn update(&mut self, msg: &Message) {
         if let Some(arg) = msg.args.clone() {
-            if let Some(i) = self.paths.get(&msg.addr) {
-                self.parameters[*i].values = arg;
+            if let Some(&i) = self.paths.get(&msg.addr) {
+                self.set_value(i, arg);
             }
         }
     }
 
     pub fn set_value(&mut self, token: ParameterIndex, value: Vec<OscType>) {
-        self.parameters[token].values = value;
+        if self.parameters[token].values != value {
+            self.parameters[token].values = value;
+            self.touch(token);
+        }
+    }
+
+    /// current revision, increases with every changed value
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    /// check if a parameter at or below an address e.g. `/wave1` changed after `revision`
+    pub fn changed_since(&self, prefix: &str, revision: u64) -> bool {
+        let sub_prefix = format!("{}/", prefix);
+        self.paths
+            .range(prefix.to_string()..)
+            .take_while(|(path, _)| path.starts_with(prefix))
+            .filter(|(path, _)| *path == prefix || path.starts_with(&sub_prefix))
+            .any(|(_, &i)| !matches!(self.revisions.get(i), Some(&r) if r <= revision))
+    }
+
+    /// make a parameter reachable under a second address, e.g. `/fog1/density` for `/fog1/perlin/scalex`
+    ///
+    /// both addresses update the same value, aliases are not announced over OSCQuery.
+    /// returns `false` if `target` does not exist or `alias` is already used
+    pub fn add_alias(&mut self, alias: &str, target: &str) -> bool {
+        match self.paths.get(target) {
+            Some(&i) if !self.paths.contains_key(alias) => {
+                self.paths.insert(alias.to_string(), i);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// addresses and current values of all parameters at or below an address e.g. `/wave1`
+    ///
+    /// sorted by address
+    pub fn values_below(&self, prefix: &str) -> Vec<(String, Vec<OscType>)> {
+        let sub_prefix = format!("{}/", prefix);
+        self.paths
+            .range(prefix.to_string()..)
+            .take_while(|(path, _)| path.starts_with(prefix))
+            .filter(|(path, _)| *path == prefix || path.starts_with(&sub_prefix))
+            .map(|(path, &i)| (path.clone(), self.parameters[i].values.clone()))
+            .collect()
+    }
+
+    /// mark a parameter as changed in a new revision
+    fn touch(&mut self, token: ParameterIndex) {
+        self.revision += 1;
+        if self.revisions.len() < self.parameters.len() {
+            self.revisions.resize(self.parameters.len(), self.revision);
+        }
+        self.revisions[token] = self.revision;
     }
 
     pub fn config_copy(&self) -> Vec<Parameter> {
         self.parameters.clone()
     }
+
+    /// removes all Parameters at or below an address e.g. `/wave1`
+    ///
+    /// the values stay inside the store, so every [ParameterIndex] stays valid,
+    /// but the parameters can no longer be reached using their address.
+    ///
+    /// * *return:* number of removed parameters
+    pub fn unregister(&mut self, prefix: &str) -> usize {
+        let before = self.paths.len();
+        let sub_prefix = format!("{}/", prefix);
+        self.paths
+            .retain(|path, _| path != prefix && !path.starts_with(&sub_prefix));
+        before - self.paths.len()
+    }
+
+    /// all parameters that can be reached using their address, in order of creation
+    fn active_parameters(&self) -> impl Iterator<Item = &Parameter> {
+        self.parameters
+            .iter()
+            .enumerate()
+            .filter(move |(i, p)| self.paths.get(&p.address) == Some(i))
+            .map(|(_, p)| p)
+    }
 }
 
 impl fmt::Display for ParameterStore {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        for p in &self.parameters {
+        for p in self.active_parameters() {
             let res = writeln!(f, "{}", p);
 
             res?;
@@ -459,7 +546,7 @@ impl ParameterStore {
         let mut root = oscq_rs::OSCNode::root(Some(Box::new(host_info)));
         //let mut root = oscq_rs::OSCNode::root(None);
         println!("create_query with {:?}", self.parameters);
-        for par in &self.parameters {
+        for par in self.active_parameters() {
             let all: Vec<oscq_rs::OscQueryParameter> = par.into();
             for p in all {
                 println!("adding into query {:?}", p);
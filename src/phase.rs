@@ -0,0 +1,40 @@
+use crate::{ParameterEnd, ParameterEndpoint, ParameterStore};
+
+///
+/// Position of an animation, moved by a speed parameter
+///
+/// integrates `speed * delta_time` every frame, so changing the speed
+/// changes how fast the position moves instead of making it jump.
+///
+/// ```ignore
+/// let mut phase = PhaseAccumulator::new(factory.build_default(1.0, "speed".to_string()));
+/// // in update
+/// let offset = phase.advance(store, ctx.delta_time);
+/// ```
+pub struct PhaseAccumulator {
+    speed: ParameterEndpoint<f32>,
+    value: f32,
+}
+
+impl PhaseAccumulator {
+    /// start at 0, moving with the `speed` parameter
+    pub fn new(speed: ParameterEndpoint<f32>) -> Self {
+        Self { speed, value: 0.0 }
+    }
+
+    /// advance by one frame with the current speed and return the new position
+    pub fn advance(&mut self, store: &ParameterStore, delta_time: f32) -> f32 {
+        self.value += self.speed(store) * delta_time;
+        self.value
+    }
+
+    /// current position
+    pub fn get(&self) -> f32 {
+        self.value
+    }
+
+    /// current speed
+    pub fn speed(&self, store: &ParameterStore) -> f32 {
+        self.speed.get(store)
+    }
+}
@@ -207,7 +207,8 @@ impl ProgramManager {
                 self.current = if let Some(p) = &up.prog.auto_next {
                     self.programs
                         .get(p)
-                        .map(|prg| Box::new(ProgramSwitcher::new(prg, time, store).unwrap()))
+                        .and_then(|prg| ProgramSwitcher::new(prg, time, store))
+                        .map(Box::new)
                 } else {
                     None
                 }
@@ -285,7 +286,12 @@ impl ProgramManager {
         self.current = self
             .programs
             .get(&p)
-            .map(|prg| Box::new(ProgramSwitcher::new(prg, time, store).unwrap()))
+            .and_then(|prg| ProgramSwitcher::new(prg, time, store))
+            .map(Box::new);
+
+        if self.current.is_none() {
+            println!("Program {} not available for the current parameters", p);
+        }
     }
 
     pub fn store(&self, path: &Path) -> Result<(), LoadStoreError> {
@@ -0,0 +1,46 @@
+use nannou::wgpu::{Device, Queue};
+
+///
+/// Everything a [crate::TextureNode] needs to render a frame
+///
+/// created by the runtime for every frame, so the same graph can run inside a nannou window,
+/// in a headless renderer or inside tests.
+/// the times are show times of a [crate::Clock], nodes use them for all animations.
+///
+/// ```ignore
+/// clock.tick(&store, update.since_last.as_secs_f32());
+/// let ctx = clock.context(window.device(), window.queue(), [1920, 1080]);
+/// tree.update(&ctx, &store);
+/// ```
+#[derive(Clone, Copy)]
+pub struct RenderContext<'a> {
+    /// render device, to create buffers and encode commands
+    pub device: &'a Device,
+    /// queue to submit the encoded commands to
+    pub queue: &'a Queue,
+    /// show time in seconds
+    pub time: f32,
+    /// show time since the last frame in seconds, 0 while paused and negative after seeking backwards
+    pub delta_time: f32,
+    /// index of the frame, starting at 0
+    pub frame: u64,
+    /// position inside the current beat, from 0 to 1
+    pub beat_phase: f32,
+    /// size of the output texture
+    pub output_size: [u32; 2],
+}
+
+impl<'a> RenderContext<'a> {
+    /// context of the first frame, with time and beat at 0
+    pub fn new(device: &'a Device, queue: &'a Queue, output_size: [u32; 2]) -> Self {
+        Self {
+            device,
+            queue,
+            time: 0.0,
+            delta_time: 0.0,
+            frame: 0,
+            beat_phase: 0.0,
+            output_size,
+        }
+    }
+}
@@ -1,61 +1,172 @@
-use ::wgpu::ShaderModuleDescriptorSpirV;
+use ::wgpu::{ShaderModuleDescriptorSpirV, TextureSampleType};
 use nannou::prelude::*;
 use nannou::wgpu::{
-    CommandEncoder, CommandEncoderDescriptor, Device, TextueSnapshot, Texture, TextureBuilder,
-    TextureCapturer, TextureUsages, TextureView,
+    CommandEncoder, Device, Queue, TextueSnapshot, Texture, TextureBuilder, TextureCapturer,
+    TextureUsages, TextureView, TextureViewId,
 };
 use std::marker::PhantomData;
+use std::num::NonZeroU32;
 
-/// Render something to a Texture using a SpirV Shader
-pub struct ShaderTarget<T, U> {
-    bind_group: wgpu::BindGroup,
-    render_pipeline: wgpu::RenderPipeline,
-    vertex_buffer: wgpu::Buffer,
-    index_buffer: wgpu::Buffer,
-    uniform_buffer: wgpu::Buffer,
-    texture: Texture,
-    uniforms: T,
-    encoder: Option<CommandEncoder>,
-    vertex_len: usize,
-    index_len: usize,
-    marker: PhantomData<U>,
+use crate::shapes::Vertex;
+use crate::{
+    check_uniform_layout, RenderContext, SamplerConfig, UniformLayout, UniformLayoutError,
+    OUTPUT_FORMAT,
+};
+
+///
+/// Configures and builds a [ShaderTarget]
+///
+/// the defaults match a full screen shader: no input textures, a single uniform buffer,
+/// no blending, a triangle strip, the vertex layout of `U` and an [OUTPUT_FORMAT] texture.
+///
+/// ```ignore
+/// let target = ShaderTargetBuilder::new(&vert, &frag, &FULL_SCREEN_QUAD, &FULL_SCREEN_QUAD_INDEX, uniform)
+///     .input_textures(2)
+///     .build(device, texture_size)?;
+/// ```
+///
+/// # Bindings
+///
+/// all resources are in bind group 0, in this order:
+///
+/// | Binding            | Resource                                         |
+/// |--------------------|--------------------------------------------------|
+/// | `0`                | uniform buffer `T`                               |
+/// | `1..=M`            | uniform buffers added with [Self::uniform_buffer]|
+/// | `M+1..=M+N`        | the `N` input textures                           |
+/// | `M+N+1..=M+2N`     | a sampler for every input texture                |
+///
+/// the layout of `T` is checked against the uniform block at binding `0` of both shaders,
+/// see [UniformLayout]. building fails with a [UniformLayoutError] if they do not match.
+pub struct ShaderTargetBuilder<'a, T, U> {
+    vert: &'a ShaderModuleDescriptorSpirV<'a>,
+    frag: &'a ShaderModuleDescriptorSpirV<'a>,
+    vertices: &'a [U],
+    indices: &'a [u16],
+    uniform: T,
+    extra_uniforms: Vec<Vec<u8>>,
+    num_input_textures: usize,
+    color_blend: wgpu::BlendComponent,
+    alpha_blend: wgpu::BlendComponent,
+    topology: wgpu::PrimitiveTopology,
+    vertex_attributes: &'a [wgpu::VertexAttribute],
+    format: wgpu::TextureFormat,
+    mip_level_count: u32,
 }
 
-impl<T, U> ShaderTarget<T, U>
+impl<'a, T, U> ShaderTargetBuilder<'a, T, U>
 where
-    T: Copy,
-    T: Clone,
-    U: Sized,
-    U: Copy,
+    T: Copy + UniformLayout,
+    U: Vertex,
 {
-    /// Create a new ShaderTarget
     /// ## Parameters
-    /// - `device`: render device [Device]
     /// - `vert`: SpirV vertex shader
-    /// - `frag`: SpirV vertex shader
-    /// - `vertecies`: for the mesh that should be rendered
-    /// - `indecies`: for the mesh that should be rendered
+    /// - `frag`: SpirV fragment shader
+    /// - `vertices`: for the mesh that should be rendered
+    /// - `indices`: for the mesh that should be rendered
     /// - `uniform` : uniform data struct that is send to the shader
     pub fn new(
-        device: &Device,
-        texture_size: [u32; 2],
-        vert: &ShaderModuleDescriptorSpirV,
-        frag: &ShaderModuleDescriptorSpirV,
-        vertecies: &[U],
-        indecies: &[u16],
+        vert: &'a ShaderModuleDescriptorSpirV<'a>,
+        frag: &'a ShaderModuleDescriptorSpirV<'a>,
+        vertices: &'a [U],
+        indices: &'a [u16],
         uniform: T,
     ) -> Self {
+        Self {
+            vert,
+            frag,
+            vertices,
+            indices,
+            uniform,
+            extra_uniforms: Vec::new(),
+            num_input_textures: 0,
+            color_blend: wgpu::BlendComponent::REPLACE,
+            alpha_blend: wgpu::BlendComponent::REPLACE,
+            topology: wgpu::PrimitiveTopology::TriangleStrip,
+            vertex_attributes: U::ATTRIBUTES,
+            format: OUTPUT_FORMAT,
+            mip_level_count: 1,
+        }
+    }
+
+    /// number of textures the shader samples, they are passed to [ShaderTarget::render_pass]
+    pub fn input_textures(mut self, num_input_textures: usize) -> Self {
+        self.num_input_textures = num_input_textures;
+        self
+    }
+
+    /// add another uniform buffer, bound after the previous ones
+    ///
+    /// it is changed with [ShaderTarget::set_extra_uniforms]
+    pub fn uniform_buffer<V: Copy>(mut self, uniform: V) -> Self {
+        self.extra_uniforms
+            .push(uniforms_as_bytes(&uniform).to_vec());
+        self
+    }
+
+    /// blending of the color channels with the previous content of the texture
+    pub fn color_blend(mut self, blend: wgpu::BlendComponent) -> Self {
+        self.color_blend = blend;
+        self
+    }
+
+    /// blending of the alpha channel with the previous content of the texture
+    pub fn alpha_blend(mut self, blend: wgpu::BlendComponent) -> Self {
+        self.alpha_blend = blend;
+        self
+    }
+
+    /// how the vertices are assembled
+    pub fn primitive_topology(mut self, topology: wgpu::PrimitiveTopology) -> Self {
+        self.topology = topology;
+        self
+    }
+
+    /// layout of the vertex buffer, replaces the layout of `U`
+    pub fn vertex_attributes(mut self, attributes: &'a [wgpu::VertexAttribute]) -> Self {
+        self.vertex_attributes = attributes;
+        self
+    }
+
+    /// format of the output texture, see [crate::NodeFormat]
+    pub fn format(mut self, format: wgpu::TextureFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// number of mip levels of the output texture, 1 by default
+    ///
+    /// [ShaderTarget::render_pass] renders level 0, the other levels are rendered
+    /// with [ShaderTarget::render_pass_to_level]
+    pub fn mip_level_count(mut self, mip_level_count: u32) -> Self {
+        self.mip_level_count = mip_level_count.max(1);
+        self
+    }
+
+    /// create the pipeline and the output texture
+    pub fn build(
+        self,
+        device: &Device,
+        texture_size: [u32; 2],
+    ) -> Result<ShaderTarget<T, U>, UniformLayoutError> {
+        // a uniform struct that does not match the shader would garble the parameters
+        for shader in [self.vert, self.frag] {
+            let label = shader.label.unwrap_or("shader");
+            check_uniform_layout::<T>(label, &shader.source, 0, 0)?;
+        }
+
         // create the shaders
         // spirv shades are compiled binary created outside this app, and could be unsafe
-        let vs_mod = unsafe { device.create_shader_module_spirv(vert) };
-        let fs_mod = unsafe { device.create_shader_module_spirv(frag) };
+        let vs_mod = unsafe { device.create_shader_module_spirv(self.vert) };
+        let fs_mod = unsafe { device.create_shader_module_spirv(self.frag) };
 
         // output format
-        let format = Frame::TEXTURE_FORMAT;
+        let format = self.format;
 
         // FrameBuffer Texture
         let texture = TextureBuilder::new()
             .size(texture_size)
+            .mip_level_count(self.mip_level_count)
             .usage(
                 TextureUsages::RENDER_ATTACHMENT
                     | TextureUsages::COPY_DST
@@ -64,9 +175,23 @@ where
             .sample_count(1)
             .format(format)
             .build(device);
+        let texture_view = texture.view().build();
+        // single levels are only needed if there is more than one
+        let level_views = match self.mip_level_count {
+            1 => Vec::new(),
+            count => (0..count)
+                .map(|level| {
+                    texture
+                        .view()
+                        .base_mip_level(level)
+                        .level_count(NonZeroU32::new(1))
+                        .build()
+                })
+                .collect(),
+        };
 
         // mesh vertex buffer
-        let vertices_bytes = vertices_as_bytes(vertecies);
+        let vertices_bytes = vertices_as_bytes(self.vertices);
         let usage = wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST;
         let vertex_buffer = device.create_buffer_init(&BufferInitDescriptor {
             label: None,
@@ -75,168 +200,285 @@ where
         });
 
         // mesh index buffer
-        let indecies_bytes = indecies_as_bytes(indecies);
+        let indices_bytes = indices_as_bytes(self.indices);
         let index_usage = wgpu::BufferUsages::INDEX | wgpu::BufferUsages::COPY_DST;
         let index_buffer = device.create_buffer_init(&BufferInitDescriptor {
             label: None,
-            contents: indecies_bytes,
+            contents: indices_bytes,
             usage: index_usage,
         });
 
-        // shader uniform buffer
-        let uniforms = uniform;
-        let uniforms_bytes = uniforms_as_bytes(&uniforms);
+        // shader uniform buffers, the one of type `T` first
         let usage = wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST;
-        let uniform_buffer = device.create_buffer_init(&BufferInitDescriptor {
-            label: None,
-            contents: uniforms_bytes,
-            usage,
-        });
+        let mut uniform_sizes = vec![std::mem::size_of::<T>()];
+        uniform_sizes.extend(self.extra_uniforms.iter().map(Vec::len));
+        let uniform_buffers = std::iter::once(uniforms_as_bytes(&self.uniform))
+            .chain(self.extra_uniforms.iter().map(Vec::as_slice))
+            .map(|contents| {
+                device.create_buffer_init(&BufferInitDescriptor {
+                    label: None,
+                    contents,
+                    usage,
+                })
+            })
+            .collect::<Vec<_>>();
+
+        // Create the samplers for sampling from the input textures.
+        // inputs are sampled with normalized coordinates, so inputs of any size
+        // are stretched to the output size, by default using linear filtering
+        let sampler_desc = SamplerConfig::default().descriptor();
+        let samplers = (0..self.num_input_textures)
+            .map(|_| device.create_sampler(&sampler_desc))
+            .collect();
 
         // create the bind and render layout
-        let bind_group_layout = wgpu::BindGroupLayoutBuilder::new()
-            .uniform_buffer(wgpu::ShaderStages::VERTEX_FRAGMENT, false)
-            .build(device);
-        let bind_group = wgpu::BindGroupBuilder::new()
-            .buffer::<T>(&uniform_buffer, 0..1)
-            .build(device, &bind_group_layout);
+        let mut bind_group_layout_builder = wgpu::BindGroupLayoutBuilder::new();
+        for _ in 0..uniform_buffers.len() {
+            bind_group_layout_builder = bind_group_layout_builder
+                .uniform_buffer(wgpu::ShaderStages::VERTEX_FRAGMENT, false);
+        }
+        //add as many texture as specified
+        for _ in 0..self.num_input_textures {
+            bind_group_layout_builder = bind_group_layout_builder.texture(
+                wgpu::ShaderStages::FRAGMENT,
+                false,
+                wgpu::TextureViewDimension::D2,
+                TextureSampleType::Float { filterable: true },
+            );
+        }
+        // filtering layouts also accept nearest samplers, so the samplers can be changed later
+        for _ in 0..self.num_input_textures {
+            bind_group_layout_builder =
+                bind_group_layout_builder.sampler(wgpu::ShaderStages::FRAGMENT, true);
+        }
+        let bind_group_layout = bind_group_layout_builder.build(device);
+
         let pipeline_layout =
             wgpu::create_pipeline_layout(device, None, &[&bind_group_layout], &[]);
         let render_pipeline = wgpu::RenderPipelineBuilder::from_layout(&pipeline_layout, &vs_mod)
             .fragment_shader(&fs_mod)
             .color_format(format)
-            .color_blend(wgpu::BlendComponent::REPLACE)
-            .alpha_blend(wgpu::BlendComponent::REPLACE)
-            .add_vertex_buffer::<U>(&nannou::wgpu::vertex_attr_array![0 => Float32x2])
+            .color_blend(self.color_blend)
+            .alpha_blend(self.alpha_blend)
+            .add_vertex_buffer::<U>(self.vertex_attributes)
             .sample_count(1)
-            .primitive_topology(wgpu::PrimitiveTopology::TriangleStrip)
+            .primitive_topology(self.topology)
             .build(device);
 
-        Self {
-            bind_group,
+        Ok(ShaderTarget {
+            bind_group_layout,
+            bind_groups: Vec::new(),
+            samplers,
+            render_pipeline,
             vertex_buffer,
             index_buffer,
-            render_pipeline,
-            uniform_buffer,
+            uniform_buffers,
+            uniform_sizes,
             texture,
-            uniforms,
-            encoder: None,
-            vertex_len: vertecies.len(),
-            index_len: indecies.len(),
+            texture_view,
+            level_views,
+            uniforms: self.uniform,
+            vertex_len: self.vertices.len(),
+            index_len: self.indices.len(),
+            vertex_capacity: vertices_bytes.len(),
+            index_capacity: indices_bytes.len(),
+            num_input_textures: self.num_input_textures,
+            // every level of a mip chain samples the previous one
+            bind_group_cache_size: BIND_GROUP_CACHE_SIZE.max(self.mip_level_count as usize + 1),
             marker: PhantomData,
-        }
+        })
     }
+}
 
-    /// begin a a new command encoder Queue using this shader
-    pub fn begin(&mut self, device: &Device) {
-        let desc = CommandEncoderDescriptor {
-            label: Some("ShaderTarget"),
-        };
-        self.encoder = Some(device.create_command_encoder(&desc));
-    }
+///
+/// Render something to a Texture using a SpirV Shader
+///
+/// the pipeline is configured with a [ShaderTargetBuilder],
+/// generators use it without input textures, combiners and effects with one or more.
+pub struct ShaderTarget<T, U> {
+    bind_group_layout: wgpu::BindGroupLayout,
+    /// bind groups of the recently used input textures, see [BIND_GROUP_CACHE_SIZE]
+    bind_groups: Vec<CachedBindGroup>,
+    /// one sampler per input texture
+    samplers: Vec<wgpu::Sampler>,
+    render_pipeline: wgpu::RenderPipeline,
+    vertex_buffer: wgpu::Buffer,
+    index_buffer: wgpu::Buffer,
+    /// the buffer of `T` followed by the extra uniform buffers
+    uniform_buffers: Vec<wgpu::Buffer>,
+    uniform_sizes: Vec<usize>,
+    texture: Texture,
+    /// view of `texture`, built once so its id stays the same for the following nodes
+    texture_view: TextureView,
+    /// views of the single mip levels, empty without mipmaps
+    level_views: Vec<TextureView>,
+    uniforms: T,
+    vertex_len: usize,
+    index_len: usize,
+    /// size of the mesh buffers in bytes
+    vertex_capacity: usize,
+    index_capacity: usize,
+    num_input_textures: usize,
+    /// number of cached bind groups, see [BIND_GROUP_CACHE_SIZE]
+    bind_group_cache_size: usize,
+    marker: PhantomData<U>,
+}
 
+impl<T, U> ShaderTarget<T, U>
+where
+    T: Copy,
+    U: Vertex,
+{
     /// change the uniforms_buffer
-    /// must be placed between begin & submit to take effect
     ///
-    /// copies the given uniform data to the GPU
-    pub fn set_uniforms(&mut self, device: &Device, uniform: T) {
+    /// the data is written to the GPU before the commands of the next submit run,
+    /// so a target uses the same uniforms for all its render passes of a frame.
+    /// this is not detected, see [crate::TextureNode::update]
+    pub fn set_uniforms(&mut self, queue: &Queue, uniform: T) {
         self.uniforms = uniform;
-        if let Some(encoder) = self.encoder.as_mut() {
-            let uniforms_size = std::mem::size_of::<T>() as wgpu::BufferAddress;
-            let uniforms_bytes = uniforms_as_bytes(&self.uniforms);
-            let usage = wgpu::BufferUsages::COPY_SRC;
-            let new_uniform_buffer = device.create_buffer_init(&BufferInitDescriptor {
-                label: None,
-                contents: uniforms_bytes,
-                usage,
-            });
-            encoder.copy_buffer_to_buffer(
-                &new_uniform_buffer,
-                0,
-                &self.uniform_buffer,
-                0,
-                uniforms_size,
-            );
+        let uniforms_bytes = uniforms_as_bytes(&self.uniforms);
+        queue.write_buffer(&self.uniform_buffers[0], 0, uniforms_bytes);
+    }
+
+    /// change an uniform buffer added with [ShaderTargetBuilder::uniform_buffer], counted from 0
+    ///
+    /// `uniform` must have the type given to the builder,
+    /// the data is written to the GPU before the next submit
+    pub fn set_extra_uniforms<V: Copy>(&mut self, queue: &Queue, index: usize, uniform: V) {
+        let uniforms_bytes = uniforms_as_bytes(&uniform);
+        match self.uniform_sizes.get(index + 1) {
+            Some(&size) if size == uniforms_bytes.len() => {}
+            Some(&size) => {
+                println!(
+                    "ShaderTarget: uniform buffer {} has {} bytes, {} given",
+                    index + 1,
+                    size,
+                    uniforms_bytes.len()
+                );
+                return;
+            }
+            None => {
+                println!("ShaderTarget: there is no extra uniform buffer {}", index);
+                return;
+            }
         }
+
+        queue.write_buffer(&self.uniform_buffers[index + 1], 0, uniforms_bytes);
     }
 
     /// change the mesh
-    /// must be placed between begin & submit to take effect
     ///
-    /// copies the given mesh data to the GPU
-    pub fn set_mesh(&mut self, device: &Device, vertecies: &[U], indecies: &[u16]) {
-        if let Some(encoder) = self.encoder.as_mut() {
-            let vertices_bytes = vertices_as_bytes(vertecies);
-            let vertex_usage = wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_SRC;
-            let new_vertex_buffer = device.create_buffer_init(&BufferInitDescriptor {
-                label: None,
-                contents: vertices_bytes,
-                usage: vertex_usage,
-            });
-
-            let indecies_bytes = indecies_as_bytes(indecies);
-            let index_usage = wgpu::BufferUsages::INDEX | wgpu::BufferUsages::COPY_SRC;
-            let new_index_buffer = device.create_buffer_init(&BufferInitDescriptor {
-                label: None,
-                contents: indecies_bytes,
-                usage: index_usage,
-            });
-
-            encoder.copy_buffer_to_buffer(
-                &new_vertex_buffer,
-                0,
-                &self.vertex_buffer,
-                0,
-                indecies_bytes.len() as wgpu::BufferAddress,
-            );
-            self.vertex_len = vertecies.len();
-
-            encoder.copy_buffer_to_buffer(
-                &new_index_buffer,
-                0,
-                &self.index_buffer,
-                0,
-                indecies_bytes.len() as wgpu::BufferAddress,
+    /// the data is written to the GPU before the next submit,
+    /// the mesh can not be larger than the one the target was built with
+    pub fn set_mesh(&mut self, queue: &Queue, vertices: &[U], indices: &[u16]) {
+        let vertices_bytes = vertices_as_bytes(vertices);
+        let indices_bytes = indices_as_bytes(indices);
+        if vertices_bytes.len() > self.vertex_capacity || indices_bytes.len() > self.index_capacity
+        {
+            println!(
+                "ShaderTarget: mesh with {} vertices and {} indices is larger than the buffers",
+                vertices.len(),
+                indices.len()
             );
-            self.index_len = indecies.len();
+            return;
         }
+
+        queue.write_buffer(&self.vertex_buffer, 0, vertices_bytes);
+        self.vertex_len = vertices.len();
+
+        queue.write_buffer(&self.index_buffer, 0, indices_bytes);
+        self.index_len = indices.len();
     }
 
-    /// render pass with shaders
-    pub fn render_pass(&mut self) {
-        if let Some(encoder) = self.encoder.as_mut() {
-            let texture_view = self.texture.view().build();
-            let mut render_pass = wgpu::RenderPassBuilder::new()
-                .color_attachment(&texture_view, |color| color)
-                .begin(encoder);
-            render_pass.set_bind_group(0, &self.bind_group, &[]);
-            render_pass.set_pipeline(&self.render_pipeline);
-            render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
-
-            // We want to draw the whole range of vertices, and we're only drawing one instance of them.
-            let vertex_range = 0..self.vertex_len as u32;
-            let instance_range = 0..1;
-            render_pass.draw(vertex_range, instance_range);
-        }
+    /// change how the input textures are sampled, one [SamplerConfig] per input texture
+    ///
+    /// inputs without a configuration use the default
+    pub fn set_samplers(&mut self, device: &Device, samplers: &[SamplerConfig]) {
+        self.samplers = (0..self.num_input_textures)
+            .map(|i| {
+                let config = samplers.get(i).copied().unwrap_or_default();
+                device.create_sampler(&config.descriptor())
+            })
+            .collect();
+        self.bind_groups.clear();
     }
 
-    /// submits the commands stored since [begin()]
-    pub fn end(&mut self, window: &Window) {
-        let encoder = self.encoder.take();
+    /// record a render pass with shaders into `encoder`, sampling the input `textures`
+    ///
+    /// nothing is rendered if less textures are given than the pipeline binds
+    pub fn render_pass(
+        &mut self,
+        device: &Device,
+        encoder: &mut CommandEncoder,
+        textures: Vec<TextureView>,
+    ) {
+        self.render_pass_to_level(device, encoder, textures, 0);
+    }
 
-        if let Some(encoder) = encoder {
-            window.queue().submit(Some(encoder.finish()));
+    /// record a render pass into mip `level` of the output texture, like [Self::render_pass]
+    ///
+    /// see [ShaderTargetBuilder::mip_level_count], nothing is rendered if the level does not exist
+    pub fn render_pass_to_level(
+        &mut self,
+        device: &Device,
+        encoder: &mut CommandEncoder,
+        textures: Vec<TextureView>,
+        level: u32,
+    ) {
+        let attachment = match self.level_views.get(level as usize) {
+            Some(view) => view.clone(),
+            None if level == 0 => self.texture_view.clone(),
+            None => {
+                println!("ShaderTarget: the texture has no mip level {}", level);
+                return;
+            }
+        };
+        if textures.len() < self.num_input_textures {
+            println!(
+                "ShaderTarget: {} input textures given, {} needed",
+                textures.len(),
+                self.num_input_textures
+            );
+            return;
         }
+
+        let index = self.bind_group_index(device, textures);
+        let bind_group = &self.bind_groups[index].bind_group;
+        let mut render_pass = wgpu::RenderPassBuilder::new()
+            .color_attachment(&attachment, |color| color)
+            .begin(encoder);
+        render_pass.set_bind_group(0, bind_group, &[]);
+        render_pass.set_pipeline(&self.render_pipeline);
+        render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+
+        // We want to draw the whole range of vertices, and we're only drawing one instance of them.
+        let vertex_range = 0..self.vertex_len as u32;
+        let instance_range = 0..1;
+        render_pass.draw(vertex_range, instance_range);
     }
 
     /// get the output texture / FrameBuffer that we renderd to
     pub fn texture_view(&self) -> TextureView {
-        self.texture.view().build()
+        self.texture_view.clone()
+    }
+
+    /// view of a single mip level of the output texture, e.g. to render the next level from it
+    ///
+    /// the whole texture if it has no mipmaps
+    pub fn level_view(&self, level: u32) -> TextureView {
+        self.level_views
+            .get(level as usize)
+            .unwrap_or(&self.texture_view)
+            .clone()
     }
 
     /// create a snapshot of the output texture, to be transferred to the CPU
-    pub fn snapshot(&self, window: &Window, texture_capturer: &TextureCapturer) -> TextueSnapshot {
-        let device = window.device();
+    pub fn snapshot(
+        &self,
+        ctx: &RenderContext,
+        texture_capturer: &TextureCapturer,
+    ) -> TextueSnapshot {
+        let device = ctx.device;
         let ce_desc = wgpu::CommandEncoderDescriptor {
             label: Some("texture capture"),
         };
@@ -244,10 +486,62 @@ where
 
         let snapshot = texture_capturer.capture(device, &mut encoder, &self.texture);
 
-        window.queue().submit(Some(encoder.finish()));
+        ctx.queue.submit(Some(encoder.finish()));
 
         snapshot
     }
+
+    /// index of the cached bind group for `textures`, it is created if not cached yet
+    fn bind_group_index(&mut self, device: &Device, textures: Vec<TextureView>) -> usize {
+        let textures: Vec<TextureView> =
+            textures.into_iter().take(self.num_input_textures).collect();
+        let ids: Vec<TextureViewId> = textures.iter().map(TextureView::id).collect();
+
+        if let Some(index) = self.bind_groups.iter().position(|cached| cached.ids == ids) {
+            return index;
+        }
+
+        if self.bind_groups.len() >= self.bind_group_cache_size {
+            self.bind_groups.remove(0);
+        }
+        let bind_group = self.build_bind_group(device, &textures);
+        self.bind_groups.push(CachedBindGroup {
+            ids,
+            _textures: textures,
+            bind_group,
+        });
+        self.bind_groups.len() - 1
+    }
+
+    /// bind the uniform buffers, the first `num_input_textures` textures and their samplers
+    fn build_bind_group(&self, device: &Device, textures: &[TextureView]) -> wgpu::BindGroup {
+        let mut bind_group_builder = wgpu::BindGroupBuilder::new();
+        for buffer in &self.uniform_buffers {
+            bind_group_builder = bind_group_builder.buffer_bytes(buffer, 0, None);
+        }
+
+        //Add as many textures as Specified
+        for item in textures.iter().take(self.num_input_textures) {
+            bind_group_builder = bind_group_builder.texture_view(item)
+        }
+        for sampler in &self.samplers {
+            bind_group_builder = bind_group_builder.sampler(sampler);
+        }
+
+        bind_group_builder.build(device, &self.bind_group_layout)
+    }
+}
+
+/// number of input texture combinations a [ShaderTarget] keeps bind groups for,
+/// e.g. a feedback alternates between two textures. targets with mipmaps keep one more per level
+const BIND_GROUP_CACHE_SIZE: usize = 4;
+
+/// bind group for a combination of input textures
+struct CachedBindGroup {
+    ids: Vec<TextureViewId>,
+    /// keeps the views alive, so their ids are not reused by other views
+    _textures: Vec<TextureView>,
+    bind_group: wgpu::BindGroup,
 }
 
 // See the `nannou::wgpu::bytes` documentation for why this is necessary.
@@ -259,7 +553,7 @@ where
     unsafe { wgpu::bytes::from_slice(data) }
 }
 // See the `nannou::wgpu::bytes` documentation for why this is necessary.
-fn indecies_as_bytes(data: &[u16]) -> &[u8] {
+fn indices_as_bytes(data: &[u16]) -> &[u8] {
     unsafe { wgpu::bytes::from_slice(data) }
 }
 
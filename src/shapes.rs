@@ -34,6 +34,12 @@ pub const FULL_TRI: [Vertex2D; 3] = [
 /// Indices of a Triangle that fills the full screen
 pub const FULL_TRI_INDEX: [u16; 3] = [1, 2, 3];
 
+/// vertex format that can be used in a [crate::shader_target::ShaderTarget]
+pub trait Vertex: Copy {
+    /// default layout of the vertex buffer
+    const ATTRIBUTES: &'static [wgpu::VertexAttribute];
+}
+
 /// simple 2D-Vector that can be send to GPU/Shader
 #[repr(C)]
 #[derive(Clone, Copy)]
@@ -54,3 +60,15 @@ pub struct Vertex3D {
 pub struct Vertex4D {
     pub position: [f32; 4],
 }
+
+impl Vertex for Vertex2D {
+    const ATTRIBUTES: &'static [wgpu::VertexAttribute] = &wgpu::vertex_attr_array![0 => Float32x2];
+}
+
+impl Vertex for Vertex3D {
+    const ATTRIBUTES: &'static [wgpu::VertexAttribute] = &wgpu::vertex_attr_array![0 => Float32x3];
+}
+
+impl Vertex for Vertex4D {
+    const ATTRIBUTES: &'static [wgpu::VertexAttribute] = &wgpu::vertex_attr_array![0 => Float32x4];
+}
@@ -0,0 +1,374 @@
+use indextree::NodeId;
+use nannou_osc::{Message, Type};
+use std::collections::{BTreeMap, HashMap};
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
+use std::time::{Duration, Instant};
+use wgpu::{CommandEncoder, Device, Queue};
+
+/// weight of a new sample in a [RollingAverage]
+const SMOOTHING: f32 = 0.1;
+
+/// maximum number of timestamps per frame, 2 per rendered node
+const MAX_TIMESTAMPS: u32 = 512;
+
+/// exponential moving average of a measured value
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RollingAverage {
+    value: Option<f32>,
+}
+
+impl RollingAverage {
+    /// add a new sample, the first sample is used as it is
+    pub fn add(&mut self, sample: f32) {
+        self.value = Some(match self.value {
+            Some(value) => value + (sample - value) * SMOOTHING,
+            None => sample,
+        });
+    }
+
+    /// current average, 0 without samples
+    pub fn get(&self) -> f32 {
+        self.value.unwrap_or_default()
+    }
+}
+
+/// averaged render times of a single node
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct NodeStats {
+    /// time spent in [crate::TextureNode::update] on the CPU
+    pub cpu_ms: f32,
+    /// time the GPU spent on the node, [None] without timestamp queries
+    pub gpu_ms: Option<f32>,
+}
+
+///
+/// Averaged timing statistics of a [crate::TextureTree]
+///
+/// # OSC Messages
+///
+/// | Address                  | Description                          |  Datatype    |
+/// |--------------------------|--------------------------------------|--------------|
+/// | `/stats/fps`             | frames per second                    | `f32`        |
+/// | `/stats/frame_ms`        | time between two updates of the tree | `f32`        |
+/// | `/stats/<node>/cpu_ms`   | CPU time of the node                 | `f32`        |
+/// | `/stats/<node>/gpu_ms`   | GPU time of the node, if supported   | `f32`        |
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TreeStats {
+    pub fps: f32,
+    pub frame_ms: f32,
+    /// statistics of all nodes that were rendered, by name
+    pub nodes: BTreeMap<String, NodeStats>,
+}
+
+impl TreeStats {
+    /// the statistics as OSC messages
+    pub fn messages(&self) -> Vec<Message> {
+        let message = |addr: String, value: f32| Message {
+            addr,
+            args: Some(vec![Type::Float(value)]),
+        };
+
+        let mut messages = vec![
+            message("/stats/fps".to_string(), self.fps),
+            message("/stats/frame_ms".to_string(), self.frame_ms),
+        ];
+        for (name, node) in &self.nodes {
+            messages.push(message(format!("/stats/{}/cpu_ms", name), node.cpu_ms));
+            if let Some(gpu_ms) = node.gpu_ms {
+                messages.push(message(format!("/stats/{}/gpu_ms", name), gpu_ms));
+            }
+        }
+        messages
+    }
+}
+
+/// records the timings while a [crate::TextureTree] is updated
+#[derive(Default)]
+pub(crate) struct StatsRecorder {
+    cpu: HashMap<NodeId, RollingAverage>,
+    gpu: HashMap<NodeId, RollingAverage>,
+    frame: RollingAverage,
+    last_frame: Option<Instant>,
+    gpu_timer: Option<GpuTimer>,
+    gpu_checked: bool,
+}
+
+impl StatsRecorder {
+    /// start a new frame, reads the GPU times of earlier frames whose timestamps are mapped
+    pub fn begin_frame(&mut self, device: &Device, queue: &Queue) {
+        let now = Instant::now();
+        if let Some(last) = self.last_frame.replace(now) {
+            self.frame.add(as_ms(now - last));
+        }
+
+        if !self.gpu_checked {
+            self.gpu_timer = GpuTimer::new(device, queue);
+            self.gpu_checked = true;
+        }
+        if let Some(timer) = self.gpu_timer.as_mut() {
+            for (id, ms) in timer.read(device) {
+                self.gpu.entry(id).or_default().add(ms);
+            }
+        }
+    }
+
+    /// called right before a node is recorded into the frame `encoder`
+    pub fn begin_node(&mut self, id: NodeId, encoder: &mut CommandEncoder) {
+        if let Some(timer) = self.gpu_timer.as_mut() {
+            timer.begin(id, encoder);
+        }
+    }
+
+    /// called right after a node was recorded, `cpu` is the time the update took
+    pub fn end_node(&mut self, id: NodeId, cpu: Duration, encoder: &mut CommandEncoder) {
+        self.cpu.entry(id).or_default().add(as_ms(cpu));
+        if let Some(timer) = self.gpu_timer.as_mut() {
+            timer.end(encoder);
+        }
+    }
+
+    /// record the resolve of the GPU timestamps of the frame
+    pub fn end_frame(&mut self, encoder: &mut CommandEncoder) {
+        if let Some(timer) = self.gpu_timer.as_mut() {
+            timer.resolve(encoder);
+        }
+    }
+
+    /// called after the frame was submitted, starts reading the GPU timestamps
+    pub fn submitted(&mut self) {
+        if let Some(timer) = self.gpu_timer.as_mut() {
+            timer.map();
+        }
+    }
+
+    /// forget a removed node
+    pub fn remove(&mut self, id: NodeId) {
+        self.cpu.remove(&id);
+        self.gpu.remove(&id);
+    }
+
+    /// collect the averages, `name` gives the name of a node
+    pub fn stats(&self, name: impl Fn(NodeId) -> String) -> TreeStats {
+        let frame_ms = self.frame.get();
+        let nodes = self
+            .cpu
+            .iter()
+            .map(|(&id, cpu)| {
+                let stats = NodeStats {
+                    cpu_ms: cpu.get(),
+                    gpu_ms: self.gpu.get(&id).map(RollingAverage::get),
+                };
+                (name(id), stats)
+            })
+            .collect();
+
+        TreeStats {
+            fps: if frame_ms > 0.0 {
+                1000.0 / frame_ms
+            } else {
+                0.0
+            },
+            frame_ms,
+            nodes,
+        }
+    }
+}
+
+///
+/// Measures the GPU time of nodes using timestamp queries
+///
+/// a timestamp is written into the frame encoder before and after every node.
+/// the results are copied into one of two read buffers and mapped without blocking,
+/// they are read in a later frame once the mapping completed.
+/// needs [wgpu::Features::TIMESTAMP_QUERY] enabled on the device
+struct GpuTimer {
+    query_set: wgpu::QuerySet,
+    resolve_buffer: wgpu::Buffer,
+    /// the timestamps of a frame are read from one buffer, while the next frame is copied into the other
+    read_buffers: [ReadBuffer; 2],
+    period: f32,
+    count: u32,
+    recording: bool,
+    nodes: Vec<NodeId>,
+}
+
+/// the result of a mapping, completed by polling the device
+type MapFuture = Pin<Box<dyn Future<Output = Result<(), wgpu::BufferAsyncError>> + Send>>;
+
+/// buffer the timestamps of a frame are read from
+struct ReadBuffer {
+    buffer: wgpu::Buffer,
+    state: ReadState,
+}
+
+enum ReadState {
+    /// can be used for the next frame
+    Free,
+    /// the copy of a frame was recorded, it is mapped after the frame was submitted
+    Copied(Vec<NodeId>, u32),
+    /// waiting for the mapping to complete
+    Mapping(Vec<NodeId>, u32, MapFuture),
+}
+
+impl GpuTimer {
+    /// [None] if the device does not support timestamp queries
+    fn new(device: &Device, queue: &Queue) -> Option<Self> {
+        if !device.features().contains(wgpu::Features::TIMESTAMP_QUERY) {
+            return None;
+        }
+
+        let query_set = device.create_query_set(&wgpu::QuerySetDescriptor {
+            label: Some("node timestamps"),
+            ty: wgpu::QueryType::Timestamp,
+            count: MAX_TIMESTAMPS,
+        });
+        let size = (MAX_TIMESTAMPS * wgpu::QUERY_SIZE) as wgpu::BufferAddress;
+        let resolve_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("node timestamps resolve"),
+            size,
+            usage: wgpu::BufferUsages::QUERY_RESOLVE | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+        let read_buffer = || ReadBuffer {
+            buffer: device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("node timestamps read"),
+                size,
+                usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            }),
+            state: ReadState::Free,
+        };
+
+        Some(Self {
+            query_set,
+            resolve_buffer,
+            read_buffers: [read_buffer(), read_buffer()],
+            period: queue.get_timestamp_period(),
+            count: 0,
+            recording: false,
+            nodes: Vec::new(),
+        })
+    }
+
+    fn begin(&mut self, id: NodeId, encoder: &mut CommandEncoder) {
+        self.recording = self.count + 2 <= MAX_TIMESTAMPS;
+        if self.recording {
+            self.nodes.push(id);
+            self.write(encoder);
+        }
+    }
+
+    fn end(&mut self, encoder: &mut CommandEncoder) {
+        if self.recording {
+            self.write(encoder);
+            self.recording = false;
+        }
+    }
+
+    /// timestamps are written between the commands of the nodes
+    fn write(&mut self, encoder: &mut CommandEncoder) {
+        encoder.write_timestamp(&self.query_set, self.count);
+        self.count += 1;
+    }
+
+    /// copy the timestamps of the frame into a free read buffer
+    ///
+    /// the frame is not measured if both buffers are still in use
+    fn resolve(&mut self, encoder: &mut CommandEncoder) {
+        let count = std::mem::take(&mut self.count);
+        let nodes = std::mem::take(&mut self.nodes);
+        let free = self
+            .read_buffers
+            .iter_mut()
+            .find(|read| matches!(read.state, ReadState::Free));
+        let read = match free {
+            Some(read) if count > 0 => read,
+            _ => return,
+        };
+
+        let size = (count * wgpu::QUERY_SIZE) as wgpu::BufferAddress;
+        encoder.resolve_query_set(&self.query_set, 0..count, &self.resolve_buffer, 0);
+        encoder.copy_buffer_to_buffer(&self.resolve_buffer, 0, &read.buffer, 0, size);
+        read.state = ReadState::Copied(nodes, count);
+    }
+
+    /// start mapping the copied timestamps, the copy must be submitted before
+    fn map(&mut self) {
+        for read in self.read_buffers.iter_mut() {
+            read.state = match std::mem::replace(&mut read.state, ReadState::Free) {
+                ReadState::Copied(nodes, count) => {
+                    let size = (count * wgpu::QUERY_SIZE) as wgpu::BufferAddress;
+                    let future = read.buffer.slice(..size).map_async(wgpu::MapMode::Read);
+                    ReadState::Mapping(nodes, count, Box::pin(future))
+                }
+                state => state,
+            };
+        }
+    }
+
+    /// GPU time of the nodes of the frames whose mapping completed in ms
+    ///
+    /// polls the device without waiting for the GPU
+    fn read(&mut self, device: &Device) -> Vec<(NodeId, f32)> {
+        device.poll(wgpu::Maintain::Poll);
+
+        let period = self.period;
+        let mut times = Vec::new();
+        for read in self.read_buffers.iter_mut() {
+            let (nodes, count, result) = match &mut read.state {
+                ReadState::Mapping(nodes, count, future) => match poll_once(future) {
+                    Poll::Ready(result) => (std::mem::take(nodes), *count, result),
+                    Poll::Pending => continue,
+                },
+                _ => continue,
+            };
+            read.state = ReadState::Free;
+            if result.is_err() {
+                continue;
+            }
+
+            let size = (count * wgpu::QUERY_SIZE) as wgpu::BufferAddress;
+            let timestamps: Vec<u64> = {
+                let data = read.buffer.slice(..size).get_mapped_range();
+                data.chunks_exact(8)
+                    .map(|bytes| {
+                        let mut word = [0u8; 8];
+                        word.copy_from_slice(bytes);
+                        u64::from_le_bytes(word)
+                    })
+                    .collect()
+            };
+            read.buffer.unmap();
+
+            times.extend(
+                nodes
+                    .into_iter()
+                    .zip(timestamps.chunks_exact(2))
+                    .map(|(id, t)| {
+                        let ns = t[1].saturating_sub(t[0]) as f32 * period;
+                        (id, ns / 1_000_000.0)
+                    }),
+            );
+        }
+        times
+    }
+}
+
+/// poll a future once without blocking, e.g. a mapping that is completed by [Device::poll]
+fn poll_once<F: Future + Unpin>(future: &mut F) -> Poll<F::Output> {
+    fn noop(_: *const ()) {}
+    fn clone(_: *const ()) -> RawWaker {
+        RawWaker::new(std::ptr::null(), &VTABLE)
+    }
+    static VTABLE: RawWakerVTable = RawWakerVTable::new(clone, noop, noop, noop);
+
+    // the waker does nothing, the future is polled again in the next frame
+    let waker = unsafe { Waker::from_raw(clone(std::ptr::null())) };
+    Pin::new(future).poll(&mut Context::from_waker(&waker))
+}
+
+fn as_ms(duration: Duration) -> f32 {
+    duration.as_secs_f32() * 1000.0
+}
@@ -1,7 +1,6 @@
-use crate::{ParameterStore, TextureNode, TextureTarget};
-use nannou::wgpu::{Device, TextureView};
-use nannou::window::Window;
-use nannou::{App, Draw};
+use crate::{InputPort, ParameterStore, RenderContext, TextureNode, TextureTarget};
+use nannou::wgpu::{CommandEncoder, Device, TextureFormat, TextureView};
+use nannou::Draw;
 
 ///
 /// Defines a Model for a [TextureModelNode]
@@ -14,13 +13,23 @@ pub trait ModelUpdate {
     /// update the model and generate a output draw from the model
     ///
     /// # Parameters
-    /// - `app`:  nannou app
+    /// - `ctx`: device, time and frame of the render
     /// - `store`: input osc parameter
     /// - `input`: vector of input textures if any
     ///
     /// # Returns:
     /// [nannou::Draw] with the drawn frame in it
-    fn update_model(&mut self, app: &App, store: &ParameterStore, input: Vec<TextureView>) -> Draw;
+    fn update_model(
+        &mut self,
+        ctx: &RenderContext,
+        store: &ParameterStore,
+        input: Vec<TextureView>,
+    ) -> Draw;
+
+    /// input ports of the model, see [TextureNode::input_ports]
+    fn input_ports(&self) -> Vec<InputPort> {
+        Vec::new()
+    }
 }
 
 /// Node that applies a Model to a texture.
@@ -36,31 +45,35 @@ where
 {
     fn update(
         &mut self,
-        app: &App,
-        window: &Window,
+        ctx: &RenderContext,
+        encoder: &mut CommandEncoder,
         store: &ParameterStore,
         input: Vec<TextureView>,
     ) {
-        let draw = self.model.update_model(app, store, input);
-        self.texture.submit(window, &draw);
+        let draw = self.model.update_model(ctx, store, input);
+        self.texture.render(ctx, encoder, &draw);
     }
 
     fn output(&self) -> TextureView {
-        self.texture.texture_view() //Building a TextureView and move it out
+        self.texture.texture_view() // the view is built once, this clones it
     }
 
     fn snapshot(
         &self,
-        window: &Window,
+        ctx: &RenderContext,
         texture_capturer: &nannou::wgpu::TextureCapturer,
     ) -> nannou::wgpu::TextueSnapshot {
-        self.texture.snapshot(window, texture_capturer)
+        self.texture.snapshot(ctx, texture_capturer)
+    }
+
+    fn input_ports(&self) -> Vec<InputPort> {
+        self.model.input_ports()
     }
 }
 
 impl<T> TextureModelNode<T> {
-    pub fn new(model: T, device: &Device, size: [u32; 2]) -> Self {
-        let texture = TextureTarget::new(device, size);
+    pub fn new(model: T, device: &Device, size: [u32; 2], format: TextureFormat) -> Self {
+        let texture = TextureTarget::new(device, size, format);
 
         Self { texture, model }
     }
@@ -1,9 +1,10 @@
 use nannou::wgpu::{
-    CommandEncoderDescriptor, Device, TextueSnapshot, Texture, TextureBuilder, TextureCapturer,
-    TextureUsages, TextureView,
+    CommandEncoder, Device, TextueSnapshot, Texture, TextureBuilder, TextureCapturer,
+    TextureFormat, TextureUsages, TextureView,
 };
-use nannou::window::Window;
-use nannou::{Draw, Frame};
+use nannou::Draw;
+
+use crate::RenderContext;
 
 ///
 /// Render to a Texture instead of the Screen
@@ -11,6 +12,8 @@ use nannou::{Draw, Frame};
 pub struct TextureTarget {
     /// The texture that we will draw to.
     texture: Texture,
+    /// view of the texture, built once so its id stays the same for the following nodes
+    texture_view: TextureView,
     /// The type used to render the `Draw` vertices to our texture.
     renderer: nannou::draw::Renderer,
 }
@@ -20,7 +23,8 @@ impl TextureTarget {
     /// ## Parameters
     /// - `device`: [Device] to bind the texture to
     /// - `texture_size`: size of the  texture / frame buffer
-    pub fn new(device: &Device, texture_size: [u32; 2]) -> Self {
+    /// - `format`: format of the texture, see [crate::NodeFormat]
+    pub fn new(device: &Device, texture_size: [u32; 2], format: TextureFormat) -> Self {
         let texture = TextureBuilder::new()
             .size(texture_size)
             // Our texture will be used as the RENDER_ATTACHMENT for our `Draw` render pass.
@@ -31,7 +35,7 @@ impl TextureTarget {
                     | TextureUsages::TEXTURE_BINDING,
             )
             // Use nannou's default multisampling sample count.
-            .format(Frame::TEXTURE_FORMAT)
+            .format(format)
             // Build it!
             .build(device);
 
@@ -40,18 +44,28 @@ impl TextureTarget {
         let renderer =
             nannou::draw::RendererBuilder::new().build_from_texture_descriptor(device, descriptor);
 
-        TextureTarget { texture, renderer }
+        let texture_view = texture.view().build();
+
+        TextureTarget {
+            texture,
+            texture_view,
+            renderer,
+        }
     }
 
     /// get an texture view to the target texture.
     /// this texture can then be used in rendering
     pub fn texture_view(&self) -> TextureView {
-        self.texture.view().build()
+        self.texture_view.clone()
     }
 
     // create a snapshot of the texture to be exported to the CPU
-    pub fn snapshot(&self, window: &Window, texture_capturer: &TextureCapturer) -> TextueSnapshot {
-        let device = window.device();
+    pub fn snapshot(
+        &self,
+        ctx: &RenderContext,
+        texture_capturer: &TextureCapturer,
+    ) -> TextueSnapshot {
+        let device = ctx.device;
         let ce_desc = wgpu::CommandEncoderDescriptor {
             label: Some("texture capture"),
         };
@@ -59,7 +73,7 @@ impl TextureTarget {
 
         let snapshot = texture_capturer.capture(device, &mut encoder, &self.texture);
 
-        window.queue().submit(Some(encoder.finish()));
+        ctx.queue.submit(Some(encoder.finish()));
 
         snapshot
     }
@@ -69,19 +83,9 @@ impl TextureTarget {
         self.texture.size()
     }
 
-    /// Submit a Draw object filled with draw commands to the renderer
-    /// and render to the texture.
-    pub fn submit(&mut self, window: &Window, draw: &Draw) {
-        let device = window.device();
-
-        let ce_desc = CommandEncoderDescriptor {
-            label: Some("texture renderer"),
-        };
-        let mut encoder = device.create_command_encoder(&ce_desc);
+    /// record the draw commands of a Draw object into `encoder`, rendering to the texture
+    pub fn render(&mut self, ctx: &RenderContext, encoder: &mut CommandEncoder, draw: &Draw) {
         self.renderer
-            .render_to_texture(device, &mut encoder, draw, &self.texture);
-
-        // Submit the commands for our drawing and texture capture to the GPU.
-        window.queue().submit(Some(encoder.finish()));
+            .render_to_texture(ctx.device, encoder, draw, &self.texture);
     }
 }
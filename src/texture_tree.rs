@@ -1,8 +1,19 @@
-use crate::ParameterStore;
+use crate::util::mipmaps::MipChain;
+use crate::{
+    Fallback, FallbackTextures, GraphError, InputPort, NodeControls, NodeFormat, NodeOptions,
+    NodeState, OutputDescription, ParameterStore, RenderContext, SamplerConfig, StatsRecorder,
+    TreeStats,
+};
 use indextree::{Arena, NodeId};
-use nannou::wgpu::{TextueSnapshot, TextureCapturer, TextureView};
-use nannou::window::Window;
-use nannou::App;
+use nannou::wgpu::{
+    CommandEncoder, CommandEncoderDescriptor, Device, TextueSnapshot, TextureCapturer, TextureView,
+};
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::time::Instant;
+
+/// slot of the [MipChain] of a feedback source, after all input slots
+const FEEDBACK_SLOT: usize = usize::MAX;
 
 ///
 /// Texture node that renders a texture
@@ -11,72 +22,975 @@ use nannou::App;
 /// This can be part of [TextureTree]
 ///
 pub trait TextureNode {
-    fn update(&mut self, app: &App, dev: &Window, store: &ParameterStore, input: Vec<TextureView>);
+    /// record the rendering of the node into the command encoder of the frame
+    ///
+    /// the encoder is submitted by the [TextureTree] after all nodes were recorded.
+    /// uniforms and meshes are written with the queue, they are only used once the encoder is
+    /// submitted: all render passes of a [ShaderTarget](crate::shader_target::ShaderTarget)
+    /// in a frame use the values set last. a node that renders passes with different uniforms
+    /// needs a target per pass, e.g. the ping-pong targets of a
+    /// [FeedbackNode](crate::effects::feedback::FeedbackNode)
+    fn update(
+        &mut self,
+        ctx: &RenderContext,
+        encoder: &mut CommandEncoder,
+        store: &ParameterStore,
+        input: Vec<TextureView>,
+    );
     fn output(&self) -> TextureView;
-    fn snapshot(&self, window: &Window, texture_capturer: &TextureCapturer) -> TextueSnapshot;
+    fn snapshot(&self, ctx: &RenderContext, texture_capturer: &TextureCapturer) -> TextueSnapshot;
+
+    /// input ports of the node, the inputs are assigned to the ports in order
+    ///
+    /// the [TextureTree] validates the wiring against the ports
+    /// and passes a fallback texture for every unconnected optional port.
+    /// the default is a node without inputs
+    fn input_ports(&self) -> Vec<InputPort> {
+        Vec::new()
+    }
+
+    /// check if input `slot` currently changes the output, e.g. a fader input with weight 0 does not
+    ///
+    /// inputs that do not contribute to any output are not rendered.
+    /// the default is that all inputs contribute
+    fn input_contributes(&self, _store: &ParameterStore, _slot: usize) -> bool {
+        true
+    }
+
+    /// check if the output changes over time, even if the parameters and inputs stay the same
+    ///
+    /// the output of nodes that are not time dependent is cached
+    /// until a parameter of the node or one of its inputs changes.
+    /// the default is time dependent
+    fn time_dependent(&self, _store: &ParameterStore) -> bool {
+        true
+    }
+
+    /// change how the input textures are sampled, one [SamplerConfig] per input port,
+    /// followed by the one of the [TextureNode::feedback_port]
+    ///
+    /// called by the [TextureTree] when the sampling of a port is changed, see [TextureTree::set_sampler].
+    /// the default ignores it
+    fn set_samplers(&mut self, _device: &Device, _samplers: &[SamplerConfig]) {}
+
+    /// port of the feedback source, its sampling can be changed like the one of an input port
+    ///
+    /// the default is a node without feedback port
+    fn feedback_port(&self) -> Option<InputPort> {
+        None
+    }
+
+    /// receives the output of the feedback source after all nodes of the frame were updated,
+    /// see [TextureTree::set_feedback]
+    ///
+    /// the default ignores it
+    fn update_feedback(
+        &mut self,
+        _ctx: &RenderContext,
+        _encoder: &mut CommandEncoder,
+        _store: &ParameterStore,
+        _source: TextureView,
+    ) {
+    }
+}
+
+///
+/// Describes a node inside a [TextureTree]
+///
+/// used to store the tree back into a [crate::GraphDescription]
+#[derive(Clone, Debug)]
+pub struct NodeInfo {
+    /// name of the node, also the base of its OSC addresses
+    pub name: String,
+    /// type name the node was created from
+    pub node_type: String,
+    /// size of the output texture
+    pub size: [u32; 2],
+    /// format of the output texture
+    pub format: NodeFormat,
+    /// node type specific options
+    pub options: NodeOptions,
 }
 
 ///
 /// TextureTree generates a Texture, based on a combination of [TextureNode]s.
 ///
-/// Each node is part of the tree.
-/// the children of a Node generate the input textures for its parent
+/// The nodes form a directed acyclic graph.
+/// Each node has an ordered list of input nodes, that generate the input textures for it.
+/// A node can be used as input of multiple nodes, it is still only rendered once per frame.
+///
+/// Next to the root, further nodes can be used as outputs,
+/// all nodes needed by the root, an output or the monitored node are rendered.
+///
+/// Every named node gets [NodeControls] to bypass, mute or solo it.
+///
+/// Feedback links pass the output of a node to another node after the frame,
+/// they are not edges of the graph, so the source can also be downstream.
+///
+/// How a node samples its inputs can be changed per port, see [TextureTree::set_sampler].
 pub struct TextureTree {
     arena: Arena<Box<dyn TextureNode>>,
     root: NodeId,
-    node_stack: Vec<NodeId>,
+    outputs: Vec<(NodeId, OutputDescription)>,
+    /// node shown by the [crate::Monitor], rendered like an output
+    monitored: Option<NodeId>,
+    inputs: HashMap<NodeId, Vec<NodeId>>,
+    order: Vec<NodeId>,
+    info: HashMap<NodeId, NodeInfo>,
+    fallback: Option<FallbackTextures>,
+    controls: HashMap<NodeId, NodeControls>,
+    states: HashMap<NodeId, NodeState>,
+    solo: Option<NodeId>,
+    solo_order: Vec<NodeId>,
+    frame: u64,
+    rendered: HashMap<NodeId, (u64, u64)>,
+    changed: HashMap<NodeId, u64>,
+    stats: StatsRecorder,
+    /// feedback node and its source
+    feedback: HashMap<NodeId, NodeId>,
+    /// sampling of the ports that differs from the default of the port, by port name
+    samplers: HashMap<NodeId, BTreeMap<String, SamplerConfig>>,
+    /// mip chains of the inputs that are sampled with mipmaps, by node and input slot
+    mipmaps: HashMap<(NodeId, usize), MipChain>,
 }
 
 impl TextureTree {
-    /// makes a runnable Tree, this tree is not editable once created.
+    /// makes a runnable Tree
+    ///
+    /// the inputs of each node are the children of the node inside the arena.
     ///
     /// **Parameter:**
     /// * arena [indextree::Arena] containing the tree structure
     /// * root: Root [NodeId] of the Tree
-    pub fn new(arena: Arena<Box<dyn TextureNode>>, root: NodeId) -> Self {
-        let node_stack = root.descendants(&arena).collect();
-        Self {
+    ///
+    /// returns the same errors as [TextureTree::from_edges]
+    pub fn new(arena: Arena<Box<dyn TextureNode>>, root: NodeId) -> Result<Self, GraphError> {
+        let inputs = root
+            .descendants(&arena)
+            .map(|id| (id, id.children(&arena).collect()))
+            .collect();
+        Self::from_edges(arena, inputs, root, HashMap::new())
+    }
+
+    /// makes a runnable graph from explicit edges
+    ///
+    /// **Parameter:**
+    /// * arena [indextree::Arena] containing the nodes, its tree structure is not used
+    /// * inputs: input nodes of each node, in input order
+    /// * root: Root [NodeId] of the graph
+    /// * info: [NodeInfo] for the nodes inside the arena
+    ///
+    /// returns [GraphError::Cycle] if the graph contains a cycle
+    /// and [GraphError::MissingInput] or [GraphError::TooManyInputs]
+    /// if the inputs of a node do not match its [InputPort]s
+    pub fn from_edges(
+        arena: Arena<Box<dyn TextureNode>>,
+        inputs: HashMap<NodeId, Vec<NodeId>>,
+        root: NodeId,
+        info: HashMap<NodeId, NodeInfo>,
+    ) -> Result<Self, GraphError> {
+        let mut tree = Self {
             arena,
             root,
-            node_stack,
+            outputs: Vec::new(),
+            monitored: None,
+            inputs,
+            order: Vec::new(),
+            info,
+            fallback: None,
+            controls: HashMap::new(),
+            states: HashMap::new(),
+            solo: None,
+            solo_order: Vec::new(),
+            frame: 0,
+            rendered: HashMap::new(),
+            changed: HashMap::new(),
+            stats: StatsRecorder::default(),
+            feedback: HashMap::new(),
+            samplers: HashMap::new(),
+            mipmaps: HashMap::new(),
+        };
+        tree.rebuild_order()?;
+
+        // nodes that are not connected to the root yet are also checked
+        let mut ids: Vec<NodeId> = tree.inputs.keys().copied().collect();
+        ids.sort();
+        for id in ids {
+            tree.check_ports(id)?;
         }
+        Ok(tree)
     }
 
-    /// run the tree
+    /// register the [NodeControls] of all named nodes that have none yet
+    ///
+    /// the controls are added after the parameters of the nodes,
+    /// so the parameter order of stored programs stays the same
+    pub fn register_controls(&mut self, store: &mut ParameterStore) {
+        let mut ids: Vec<NodeId> = self
+            .info
+            .keys()
+            .filter(|id| !self.controls.contains_key(id))
+            .copied()
+            .collect();
+        ids.sort();
+
+        for id in ids {
+            let controls = NodeControls::new(self.node_name(id), store);
+            self.controls.insert(id, controls);
+        }
+    }
+
+    /// update order of all nodes needed by `start`, the outputs and the monitored node,
+    /// every node comes after all of its inputs
     ///
-    /// updates all [TextureNode]s inside the tree, beginning with the leaf, ending at the Root
-    pub fn update(&mut self, app: &App, win: &Window, store: &ParameterStore) {
-        //this order of update guaranies that all children are updated before its children
-        for &n_id in self.node_stack.iter().rev() {
-            // collect updated children when available
-            let children_outputs: Vec<TextureView> = {
-                n_id.children(&self.arena)
-                    .map(|id| self.arena.get(id).unwrap().get().output())
-                    .collect()
+    /// the ports of all nodes in the order are checked
+    fn topological_order(&self, start: NodeId) -> Result<Vec<NodeId>, GraphError> {
+        let mut order = Vec::new();
+        let mut marks = HashMap::new();
+        self.visit(start, &mut marks, &mut order)?;
+        for output in self.outputs.iter().map(|&(id, _)| id).chain(self.monitored) {
+            self.visit(output, &mut marks, &mut order)?;
+        }
+        for &id in &order {
+            self.check_ports(id)?;
+        }
+        Ok(order)
+    }
+
+    /// depth first search, `marks` is `false` while a node is visited and `true` when it is done
+    fn visit(
+        &self,
+        id: NodeId,
+        marks: &mut HashMap<NodeId, bool>,
+        order: &mut Vec<NodeId>,
+    ) -> Result<(), GraphError> {
+        match marks.get(&id) {
+            Some(true) => return Ok(()),
+            Some(false) => return Err(GraphError::Cycle(self.node_name(id))),
+            None => {}
+        }
+        if self.arena.get(id).filter(|n| !n.is_removed()).is_none() {
+            return Err(GraphError::UnknownNode(self.node_name(id)));
+        }
+
+        marks.insert(id, false);
+        for &input in self.inputs.get(&id).into_iter().flatten() {
+            self.visit(input, marks, order)?;
+        }
+        marks.insert(id, true);
+        order.push(id);
+
+        Ok(())
+    }
+
+    /// check the inputs of a node against its [InputPort]s
+    fn check_ports(&self, id: NodeId) -> Result<(), GraphError> {
+        let node = match self.arena.get(id).filter(|n| !n.is_removed()) {
+            Some(node) => node.get(),
+            None => return Ok(()),
+        };
+        let ports = node.input_ports();
+        let connected = self.inputs.get(&id).map_or(0, Vec::len);
+
+        let capacity = ports
+            .iter()
+            .fold(0, |sum: usize, p| sum.saturating_add(p.count));
+        if connected > capacity {
+            return Err(GraphError::TooManyInputs(
+                self.node_name(id),
+                connected,
+                capacity,
+            ));
+        }
+
+        let mut slot = 0;
+        for port in ports {
+            if port.required && connected <= slot {
+                return Err(GraphError::MissingInput(self.node_name(id), port.name));
+            }
+            slot = slot.saturating_add(port.count);
+        }
+
+        Ok(())
+    }
+
+    /// output textures of the inputs of a node, one entry per connected input,
+    /// with the sampling of their port. unconnected ports get their fallback texture
+    fn input_textures(&self, id: NodeId) -> Vec<(TextureView, SamplerConfig)> {
+        let inputs = self.inputs.get(&id).map(Vec::as_slice).unwrap_or_default();
+        let ports = self.arena.get(id).unwrap().get().input_ports();
+        let samplers = self.port_samplers(id, &ports);
+
+        let mut textures = Vec::new();
+        let mut slot = 0;
+        for (port, sampler) in ports.into_iter().zip(samplers) {
+            let connected: Vec<(TextureView, SamplerConfig)> = inputs
+                .iter()
+                .skip(slot)
+                .take(port.count)
+                .map(|&input| (self.texture_of(input), sampler))
+                .collect();
+            if connected.is_empty() {
+                if let Some(fallback) = &self.fallback {
+                    textures.push((fallback.texture_view(port.fallback), sampler));
+                }
+            }
+            textures.extend(connected);
+            slot = slot.saturating_add(port.count);
+        }
+        textures
+    }
+
+    /// sampling of each of the `ports` of a node, the default of the port if it was not changed
+    fn port_samplers(&self, id: NodeId, ports: &[InputPort]) -> Vec<SamplerConfig> {
+        let changed = self.samplers.get(&id);
+        ports
+            .iter()
+            .map(|port| {
+                changed
+                    .and_then(|samplers| samplers.get(&port.name))
+                    .copied()
+                    .unwrap_or(port.sampler)
+            })
+            .collect()
+    }
+
+    /// node whose output is used as output of `id`, [None] if the output is black
+    ///
+    /// follows the first input of bypassed nodes
+    fn resolve(&self, id: NodeId) -> Option<NodeId> {
+        match self.states.get(&id) {
+            Some(NodeState::Mute) => None,
+            Some(NodeState::Bypass) => self
+                .inputs
+                .get(&id)
+                .and_then(|inputs| inputs.first())
+                .and_then(|&input| self.resolve(input)),
+            _ => Some(id),
+        }
+    }
+
+    /// output texture of a node, after applying bypass and mute
+    fn texture_of(&self, id: NodeId) -> TextureView {
+        match self.resolve(id) {
+            Some(node) => self.arena.get(node).unwrap().get().output(),
+            None => self
+                .fallback
+                .as_ref()
+                .expect("fallback textures are created by the first update")
+                .texture_view(Fallback::Black),
+        }
+    }
+
+    /// the soloed node, or the root if no node is soloed
+    fn output_node(&self) -> NodeId {
+        match self.solo {
+            Some(solo) if !self.solo_order.is_empty() => solo,
+            _ => self.root,
+        }
+    }
+
+    /// read the [NodeControls] of all nodes for this frame
+    ///
+    /// if several nodes are soloed the one created first is used
+    fn update_controls(&mut self, store: &ParameterStore) {
+        self.states = self
+            .controls
+            .iter()
+            .map(|(&id, controls)| (id, controls.state(store)))
+            .collect();
+
+        let solo = self
+            .controls
+            .iter()
+            .filter(|(_, controls)| controls.solo(store))
+            .map(|(&id, _)| id)
+            .min();
+
+        if solo != self.solo {
+            self.solo = solo;
+            self.solo_order = match solo.map(|id| self.topological_order(id)) {
+                Some(Ok(order)) => order,
+                Some(Err(e)) => {
+                    println!("solo_error: {}", e);
+                    Vec::new()
+                }
+                None => Vec::new(),
             };
+        }
+    }
+
+    /// nodes that contribute to the output, one of the additional outputs or the monitored node
+    ///
+    /// follows only the inputs a node reports as contributing,
+    /// the first input of bypassed nodes and no inputs of muted nodes
+    fn visible_nodes(&self, store: &ParameterStore) -> HashSet<NodeId> {
+        let mut visible = HashSet::new();
+        let mut stack: Vec<NodeId> = std::iter::once(self.output_node())
+            .chain(self.outputs.iter().map(|&(id, _)| id))
+            .chain(self.monitored)
+            .collect();
+
+        while let Some(id) = stack.pop() {
+            if !visible.insert(id) {
+                continue;
+            }
+            let inputs = self.inputs.get(&id).map(Vec::as_slice).unwrap_or_default();
+            match self.states.get(&id) {
+                Some(NodeState::Mute) => {}
+                Some(NodeState::Bypass) => stack.extend(inputs.first()),
+                _ => {
+                    let node = self.arena.get(id).unwrap().get();
+                    stack.extend(
+                        inputs
+                            .iter()
+                            .enumerate()
+                            .filter(|&(slot, _)| node.input_contributes(store, slot))
+                            .map(|(_, &input)| input),
+                    );
+                }
+            }
+        }
+        visible
+    }
+
+    /// check if the output of a node has to be rendered again
+    ///
+    /// a cached output is used if the node is not time dependent,
+    /// and neither its parameters nor its inputs changed since it was rendered
+    fn needs_update(&self, id: NodeId, store: &ParameterStore) -> bool {
+        let (frame, revision) = match self.rendered.get(&id) {
+            Some(&rendered) => rendered,
+            None => return true,
+        };
+
+        let active = !matches!(
+            self.states.get(&id),
+            Some(NodeState::Bypass | NodeState::Mute)
+        );
+        let time_dependent = active && self.arena.get(id).unwrap().get().time_dependent(store);
+        let parameters_changed = match self.info.get(&id) {
+            Some(info) => store.changed_since(&format!("/{}", info.name), revision),
+            None => true,
+        };
+        let inputs_changed = self
+            .inputs
+            .get(&id)
+            .into_iter()
+            .flatten()
+            .any(|input| matches!(self.changed.get(input), Some(&c) if c > frame));
+
+        time_dependent || parameters_changed || inputs_changed
+    }
+
+    /// name of a node for messages, falls back to the [NodeId]
+    pub(crate) fn node_name(&self, id: NodeId) -> String {
+        match self.info.get(&id) {
+            Some(info) => info.name.clone(),
+            None => format!("{:?}", id),
+        }
+    }
+
+    /// run the tree
+    ///
+    /// updates all [TextureNode]s inside the tree in topological order, ending at the Root.
+    /// if a node is soloed only the nodes needed by it are updated.
+    /// skipped are
+    /// - bypassed and muted nodes
+    /// - nodes that do not contribute to an output, see [TextureNode::input_contributes]
+    /// - nodes with a cached output, see [TextureNode::time_dependent]
+    ///
+    /// all nodes are recorded into one command encoder that is submitted once.
+    /// the render times of the nodes are recorded, see [TextureTree::stats]
+    pub fn update(&mut self, ctx: &RenderContext, store: &ParameterStore) {
+        let (device, queue) = (ctx.device, ctx.queue);
+        self.stats.begin_frame(device, queue);
+
+        let ce_desc = CommandEncoderDescriptor {
+            label: Some("frame"),
+        };
+        let mut encoder = device.create_command_encoder(&ce_desc);
+        self.record(ctx, &mut encoder, store);
+        self.stats.end_frame(&mut encoder);
+
+        queue.submit(Some(encoder.finish()));
+        self.stats.submitted();
+    }
+
+    /// record the nodes into `encoder` without submitting it, see [TextureTree::update]
+    ///
+    /// used for a tree inside a [crate::GroupNode], it is rendered with the frame of the outer tree.
+    /// GPU times are only measured by [TextureTree::update]
+    pub fn record(
+        &mut self,
+        ctx: &RenderContext,
+        encoder: &mut CommandEncoder,
+        store: &ParameterStore,
+    ) {
+        if self.fallback.is_none() {
+            self.fallback = Some(FallbackTextures::new(ctx.device, ctx.queue));
+        }
+
+        //this order of update guaranies that all inputs are updated before the node itself
+        for n_id in self.schedule(store) {
+            // collect updated inputs, inputs sampled with mipmaps are copied into their mip chain
+            let inputs = self.input_textures(n_id);
+            let mipmaps = &mut self.mipmaps;
+            let input_outputs = inputs
+                .into_iter()
+                .enumerate()
+                .map(|(slot, (texture, sampler))| {
+                    if !sampler.mipmaps {
+                        return texture;
+                    }
+                    update_mip_chain(mipmaps, (n_id, slot), ctx, encoder, texture)
+                })
+                .collect();
 
             //get the node to update
             let node = { self.arena.get_mut(n_id).unwrap().get_mut() };
 
             // update the node
-            node.update(app, win, store, children_outputs);
+            self.stats.begin_node(n_id, encoder);
+            let start = Instant::now();
+            node.update(ctx, encoder, store, input_outputs);
+            self.stats.end_node(n_id, start.elapsed(), encoder);
         }
+        self.update_feedback(ctx, encoder, store);
     }
 
-    /// get the texture output of the root node
+    /// start the next frame and select the nodes to render, in update order
+    ///
+    /// all nodes that need an update are marked as changed,
+    /// bypassed and muted nodes are marked but not returned.
+    /// called by [TextureTree::update], the scheduling can be checked without a device
+    pub fn schedule(&mut self, store: &ParameterStore) -> Vec<NodeId> {
+        self.update_controls(store);
+        self.frame += 1;
+        let visible = self.visible_nodes(store);
+
+        let order = if self.solo_order.is_empty() {
+            self.order.clone()
+        } else {
+            self.solo_order.clone()
+        };
+
+        let mut scheduled = Vec::new();
+        for n_id in order {
+            if !visible.contains(&n_id) || !self.needs_update(n_id, store) {
+                continue;
+            }
+            self.changed.insert(n_id, self.frame);
+            self.rendered.insert(n_id, (self.frame, store.revision()));
+
+            if !matches!(
+                self.states.get(&n_id),
+                Some(NodeState::Bypass | NodeState::Mute)
+            ) {
+                scheduled.push(n_id);
+            }
+        }
+        scheduled
+    }
+
+    /// pass the sources to all feedback nodes that were updated in this frame
+    fn update_feedback(
+        &mut self,
+        ctx: &RenderContext,
+        encoder: &mut CommandEncoder,
+        store: &ParameterStore,
+    ) {
+        let mut links: Vec<(NodeId, NodeId)> = self
+            .feedback
+            .iter()
+            .map(|(&node, &source)| (node, source))
+            .filter(|(node, _)| matches!(self.rendered.get(node), Some(&(f, _)) if f == self.frame))
+            .filter(|(node, _)| matches!(self.states.get(node), None | Some(NodeState::Active)))
+            .collect();
+        links.sort();
+
+        for (node, source) in links {
+            let mut texture = self.texture_of(source);
+            let port = self.arena.get(node).unwrap().get().feedback_port();
+            let mipmaps = match port {
+                Some(port) => self.port_samplers(node, &[port])[0].mipmaps,
+                None => false,
+            };
+            if mipmaps {
+                let key = (node, FEEDBACK_SLOT);
+                texture = update_mip_chain(&mut self.mipmaps, key, ctx, encoder, texture);
+            }
+
+            let node = self.arena.get_mut(node).unwrap().get_mut();
+            node.update_feedback(ctx, encoder, store, texture);
+        }
+    }
+
+    /// averaged frame and render times of the nodes
+    ///
+    /// GPU times are only measured if the device has [nannou::wgpu::Features::TIMESTAMP_QUERY] enabled
+    pub fn stats(&self) -> TreeStats {
+        self.stats.stats(|id| self.node_name(id))
+    }
+
+    /// get the texture output of the root node, or of the soloed node
     pub fn output(&self) -> TextureView {
-        self.arena.get(self.root).unwrap().get().output()
+        self.texture_of(self.output_node())
     }
 
     pub fn snapshot(
         &self,
-        window: &Window,
+        ctx: &RenderContext,
         texture_capturer: &nannou::wgpu::TextureCapturer,
     ) -> nannou::wgpu::TextueSnapshot {
+        self.node_snapshot(self.output_node(), ctx, texture_capturer)
+    }
+
+    /// get the texture output of any node of the graph
+    ///
+    /// only nodes contributing to an output or the monitored node are rendered,
+    /// other nodes show their last rendered frame, see [TextureTree::set_monitored]
+    pub fn node_output(&self, node: NodeId) -> TextureView {
+        self.texture_of(node)
+    }
+
+    /// capture the texture output of any node of the graph, see [TextureTree::node_output]
+    pub fn node_snapshot(
+        &self,
+        node: NodeId,
+        ctx: &RenderContext,
+        texture_capturer: &nannou::wgpu::TextureCapturer,
+    ) -> nannou::wgpu::TextueSnapshot {
+        match self.resolve(node) {
+            Some(node) => self
+                .arena
+                .get(node)
+                .unwrap()
+                .get()
+                .snapshot(ctx, texture_capturer),
+            None => self
+                .fallback
+                .as_ref()
+                .expect("fallback textures are created by the first update")
+                .snapshot(ctx, texture_capturer, Fallback::Black),
+        }
+    }
+
+    /// [NodeId] of the root node
+    pub fn root(&self) -> NodeId {
+        self.root
+    }
+
+    /// additional output nodes, with the description of their stream
+    pub fn outputs(&self) -> impl Iterator<Item = (NodeId, &OutputDescription)> + '_ {
+        self.outputs.iter().map(|(id, output)| (*id, output))
+    }
+
+    /// use a node as additional output, the node and its inputs are rendered every frame
+    pub fn add_output(
+        &mut self,
+        node: NodeId,
+        output: OutputDescription,
+    ) -> Result<(), GraphError> {
+        if self.outputs.iter().any(|(_, o)| o.name == output.name) {
+            return Err(GraphError::DuplicateOutput(output.name));
+        }
+
+        self.outputs.push((node, output));
+        let result = self.rebuild_order();
+        if result.is_err() {
+            self.outputs.pop();
+        }
+        result
+    }
+
+    /// node shown by the [crate::Monitor], see [TextureTree::set_monitored]
+    pub fn monitored(&self) -> Option<NodeId> {
+        self.monitored
+    }
+
+    /// render a node and its inputs every frame like an output, [None] monitors the output
+    ///
+    /// the node is kept if it can not be rendered, e.g. because a required input is missing
+    pub fn set_monitored(&mut self, node: Option<NodeId>) -> Result<(), GraphError> {
+        let old = std::mem::replace(&mut self.monitored, node);
+        let result = self.rebuild_order();
+        if result.is_err() {
+            self.monitored = old;
+        }
+        result
+    }
+
+    /// all rendered nodes of the graph in update order, every node after its inputs
+    pub fn nodes(&self) -> impl Iterator<Item = NodeId> + '_ {
+        self.order.iter().copied()
+    }
+
+    /// all nodes of the graph in creation order, also the ones that are not rendered
+    pub fn all_nodes(&self) -> impl Iterator<Item = NodeId> + '_ {
+        self.arena
+            .iter()
+            .filter(|node| !node.is_removed())
+            .filter_map(move |node| self.arena.get_node_id(node))
+    }
+
+    /// the nodes that are used as input of a node, in input order
+    pub fn inputs(&self, node: NodeId) -> Vec<NodeId> {
+        self.inputs.get(&node).cloned().unwrap_or_default()
+    }
+
+    /// mutable access to a node, e.g. to pass the inputs of a [crate::GroupNode] to its inner nodes
+    pub fn node_mut(&mut self, node: NodeId) -> Option<&mut (dyn TextureNode + 'static)> {
+        self.arena.get_mut(node).map(|n| n.get_mut().as_mut())
+    }
+
+    /// pass the output of `source` to `node` after every frame, [None] removes the feedback
+    ///
+    /// see [TextureNode::update_feedback]
+    pub fn set_feedback(&mut self, node: NodeId, source: Option<NodeId>) -> Result<(), GraphError> {
+        for id in std::iter::once(node).chain(source) {
+            if self.arena.get(id).filter(|n| !n.is_removed()).is_none() {
+                return Err(GraphError::UnknownNode(self.node_name(id)));
+            }
+        }
+        match source {
+            Some(source) => self.feedback.insert(node, source),
+            None => self.feedback.remove(&node),
+        };
+        Ok(())
+    }
+
+    /// the feedback source of a node
+    pub fn feedback_source(&self, node: NodeId) -> Option<NodeId> {
+        self.feedback.get(&node).copied()
+    }
+
+    /// change how `node` samples the inputs of `port`, see [SamplerConfig]
+    ///
+    /// `port` can also be the [TextureNode::feedback_port] of the node.
+    /// returns [GraphError::UnknownPort] if the node has no port with this name
+    pub fn set_sampler(
+        &mut self,
+        device: &Device,
+        node: NodeId,
+        port: &str,
+        sampler: SamplerConfig,
+    ) -> Result<(), GraphError> {
+        if self.arena.get(node).filter(|n| !n.is_removed()).is_none() {
+            return Err(GraphError::UnknownNode(self.node_name(node)));
+        }
+        let mut ports = self.input_ports(node);
+        ports.extend(self.arena.get(node).unwrap().get().feedback_port());
+        let default = match ports.iter().find(|p| p.name == port) {
+            Some(p) => p.sampler,
+            None => {
+                return Err(GraphError::UnknownPort(
+                    self.node_name(node),
+                    port.to_string(),
+                ))
+            }
+        };
+
+        let samplers = self.samplers.entry(node).or_default();
+        if sampler == default {
+            samplers.remove(port);
+        } else {
+            samplers.insert(port.to_string(), sampler);
+        }
+
+        let samplers = self.port_samplers(node, &ports);
+        let n = self.arena.get_mut(node).unwrap().get_mut();
+        n.set_samplers(device, &samplers);
+        self.mipmaps.retain(|&(id, _), _| id != node);
+        // the cached output was sampled differently
+        self.rendered.remove(&node);
+        Ok(())
+    }
+
+    /// the ports of a node whose sampling differs from the default of the port
+    pub fn samplers(&self, node: NodeId) -> Option<&BTreeMap<String, SamplerConfig>> {
+        self.samplers
+            .get(&node)
+            .filter(|samplers| !samplers.is_empty())
+    }
+
+    /// the [InputPort]s of a node, empty for unknown nodes
+    pub fn input_ports(&self, node: NodeId) -> Vec<InputPort> {
         self.arena
-            .get(self.root)
-            .unwrap()
-            .get()
-            .snapshot(window, texture_capturer)
+            .get(node)
+            .map(|n| n.get().input_ports())
+            .unwrap_or_default()
     }
+
+    /// name and type of a node, if known
+    pub fn node_info(&self, node: NodeId) -> Option<&NodeInfo> {
+        self.info.get(&node)
+    }
+
+    /// find a node by its name
+    pub fn find(&self, name: &str) -> Option<NodeId> {
+        self.info
+            .iter()
+            .find(|(_, info)| info.name == name)
+            .map(|(&id, _)| id)
+    }
+
+    /// add a new node to the graph
+    ///
+    /// the node is not rendered until it is connected to the root
+    pub fn add_node(
+        &mut self,
+        node: Box<dyn TextureNode>,
+        info: NodeInfo,
+    ) -> Result<NodeId, GraphError> {
+        if self.find(&info.name).is_some() {
+            return Err(GraphError::DuplicateNode(info.name));
+        }
+        let id = self.arena.new_node(node);
+        self.info.insert(id, info);
+        Ok(id)
+    }
+
+    /// use `input` as input `slot` of `node`
+    ///
+    /// replaces the current input of the slot, or adds a new input if `slot` is the next free slot.
+    /// the edit is reverted if it would create a cycle or the node has no port for the input
+    pub fn connect(&mut self, node: NodeId, input: NodeId, slot: usize) -> Result<(), GraphError> {
+        let inputs = self.inputs.entry(node).or_default();
+        let replaced = match slot {
+            s if s < inputs.len() => Some(std::mem::replace(&mut inputs[s], input)),
+            s if s == inputs.len() => {
+                inputs.push(input);
+                None
+            }
+            _ => return Err(GraphError::InvalidSlot(self.node_name(node), slot)),
+        };
+
+        let result = self.rebuild_order();
+        if result.is_err() {
+            let inputs = self.inputs.get_mut(&node).unwrap();
+            match replaced {
+                Some(old) => inputs[slot] = old,
+                None => {
+                    inputs.pop();
+                }
+            }
+        }
+        result
+    }
+
+    /// remove input `slot` of `node`, the following inputs move up one slot
+    ///
+    /// the edit is reverted if a required input of a rendered node would be missing
+    pub fn disconnect(&mut self, node: NodeId, slot: usize) -> Result<(), GraphError> {
+        let removed = match self.inputs.get_mut(&node) {
+            Some(inputs) if slot < inputs.len() => inputs.remove(slot),
+            _ => return Err(GraphError::InvalidSlot(self.node_name(node), slot)),
+        };
+
+        let result = self.rebuild_order();
+        if result.is_err() {
+            self.inputs.get_mut(&node).unwrap().insert(slot, removed);
+        }
+        result
+    }
+
+    /// remove a node from the graph, it is also removed from the inputs of all other nodes
+    ///
+    /// the root node, output nodes and required inputs of rendered nodes can not be removed.
+    /// feedback links from and to the node are removed, a monitored node is no longer monitored
+    pub fn remove_node(&mut self, node: NodeId) -> Result<Option<NodeInfo>, GraphError> {
+        if node == self.root {
+            return Err(GraphError::RootNode(self.node_name(node)));
+        }
+        if self.outputs.iter().any(|&(output, _)| output == node) {
+            return Err(GraphError::OutputNode(self.node_name(node)));
+        }
+
+        let old_inputs = self.inputs.clone();
+        let old_monitored = self.monitored;
+        for inputs in self.inputs.values_mut() {
+            inputs.retain(|&i| i != node);
+        }
+        self.inputs.remove(&node);
+        if self.monitored == Some(node) {
+            self.monitored = None;
+        }
+
+        if let Err(e) = self.rebuild_order() {
+            self.inputs = old_inputs;
+            self.monitored = old_monitored;
+            return Err(e);
+        }
+
+        node.remove(&mut self.arena);
+        self.controls.remove(&node);
+        self.stats.remove(node);
+        self.feedback.remove(&node);
+        self.feedback.retain(|_, &mut source| source != node);
+        self.samplers.remove(&node);
+        self.mipmaps.retain(|&(id, _), _| id != node);
+        Ok(self.info.remove(&node))
+    }
+
+    /// use another node as output of the graph
+    pub fn set_root(&mut self, node: NodeId) -> Result<(), GraphError> {
+        let old = std::mem::replace(&mut self.root, node);
+        let result = self.rebuild_order();
+        if result.is_err() {
+            self.root = old;
+        }
+        result
+    }
+
+    /// check that there is no cycle, also among nodes that are not rendered
+    fn check_acyclic(&self) -> Result<(), GraphError> {
+        let mut ids: Vec<NodeId> = self.inputs.keys().copied().collect();
+        ids.sort();
+
+        let mut marks = HashMap::new();
+        let mut order = Vec::new();
+        for id in ids {
+            self.visit(id, &mut marks, &mut order)?;
+        }
+        Ok(())
+    }
+
+    /// update the topological order after the graph was edited
+    ///
+    /// the whole graph must be acyclic and the ports of all rendered nodes are checked,
+    /// the order is not changed on error
+    fn rebuild_order(&mut self) -> Result<(), GraphError> {
+        self.check_acyclic()?;
+        self.order = self.topological_order(self.root)?;
+        // the solo order is rebuilt and all nodes are rendered with the next update
+        self.solo = None;
+        self.solo_order.clear();
+        self.rendered.clear();
+        self.changed.clear();
+        Ok(())
+    }
+}
+
+/// copy `texture` into the [MipChain] of `key`, the chain is created on first use
+///
+/// returns the texture with all mip levels, or `texture` itself if the chain can not be created
+fn update_mip_chain(
+    mipmaps: &mut HashMap<(NodeId, usize), MipChain>,
+    key: (NodeId, usize),
+    ctx: &RenderContext,
+    encoder: &mut CommandEncoder,
+    texture: TextureView,
+) -> TextureView {
+    let chain = match mipmaps.entry(key) {
+        Entry::Occupied(entry) => entry.into_mut(),
+        Entry::Vacant(entry) => match MipChain::new(ctx.device, texture.size()) {
+            Ok(chain) => entry.insert(chain),
+            Err(e) => {
+                println!("MipChain: {}", e);
+                return texture;
+            }
+        },
+    };
+    chain.update(ctx, encoder, texture)
 }
@@ -0,0 +1,371 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::Display;
+
+/// member of a uniform block, with its byte offset and size
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UniformMember {
+    pub name: String,
+    pub offset: usize,
+    pub size: usize,
+}
+
+///
+/// Uniform struct whose layout can be checked against the uniform block of a shader
+///
+/// implemented with [uniform_layout!](crate::uniform_layout), the members are in declaration order.
+/// the [crate::shader_target::ShaderTargetBuilder] checks the struct against both shaders,
+/// so a struct that does not match the std140 layout of its block fails when the shader is loaded
+/// instead of silently garbling parameters.
+pub trait UniformLayout {
+    fn members() -> Vec<UniformMember>;
+}
+
+///
+/// Implements [UniformLayout] for a `#[repr(C)]` struct, all fields must be listed in order
+///
+/// ```ignore
+/// #[repr(C)]
+/// #[derive(Clone, Copy)]
+/// struct UniformsColorRamp {
+///     c0: [f32; 3],
+///     f0: f32,
+/// }
+/// uniform_layout!(UniformsColorRamp { c0, f0 });
+/// ```
+#[macro_export]
+macro_rules! uniform_layout {
+    ($t:ident { $($field:ident),* $(,)? }) => {
+        impl $crate::UniformLayout for $t {
+            fn members() -> Vec<$crate::UniformMember> {
+                // fails to compile if a field is not listed
+                let _ = |uniform: $t| {
+                    let $t { $($field: _),* } = uniform;
+                };
+                let uninit = std::mem::MaybeUninit::<$t>::uninit();
+                let base = uninit.as_ptr();
+                vec![$({
+                    // only the address of the field is used, it is not read
+                    let field = unsafe { std::ptr::addr_of!((*base).$field) };
+                    $crate::UniformMember {
+                        name: stringify!($field).to_string(),
+                        offset: field as usize - base as usize,
+                        size: $crate::size_of_pointee(field),
+                    }
+                }),*]
+            }
+        }
+    };
+}
+
+/// size of the value behind a pointer, used by [uniform_layout!](crate::uniform_layout)
+#[doc(hidden)]
+pub fn size_of_pointee<T>(_: *const T) -> usize {
+    std::mem::size_of::<T>()
+}
+
+/// error of [check_uniform_layout]
+#[derive(Debug)]
+pub enum UniformLayoutError {
+    InvalidSpirv(String),
+    /// shader, Rust type and a description of every mismatching member
+    Mismatch(String, String, Vec<String>),
+}
+
+impl Display for UniformLayoutError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UniformLayoutError::InvalidSpirv(e) => write!(f, "invalid SPIR-V: {}", e),
+            UniformLayoutError::Mismatch(shader, t, members) => {
+                write!(
+                    f,
+                    "uniform block of '{}' does not match {}:\n  {}",
+                    shader,
+                    t,
+                    members.join("\n  ")
+                )
+            }
+        }
+    }
+}
+
+impl Error for UniformLayoutError {}
+
+/// check the uniform struct `T` against the uniform block at `set` and `binding` of a SPIR-V shader
+///
+/// the members are compared in order, their offsets and sizes must be the same.
+/// a shader that does not use the binding matches every struct.
+/// `shader` is only used in the error message
+pub fn check_uniform_layout<T: UniformLayout>(
+    shader: &str,
+    spirv: &[u32],
+    set: u32,
+    binding: u32,
+) -> Result<(), UniformLayoutError> {
+    let block = match reflect_uniform_block(spirv, set, binding)? {
+        Some(block) => block,
+        None => return Ok(()),
+    };
+    let members = T::members();
+
+    let mut mismatches = Vec::new();
+    for i in 0..block.len().max(members.len()) {
+        match (block.get(i), members.get(i)) {
+            (Some(s), Some(r)) if s.offset == r.offset && s.size == r.size => {}
+            (Some(s), Some(r)) => mismatches.push(format!(
+                "'{}' has offset {} and size {} in the shader, '{}' has offset {} and size {}",
+                s.name, s.offset, s.size, r.name, r.offset, r.size
+            )),
+            (Some(s), None) => mismatches.push(format!(
+                "'{}' at offset {} is missing in the struct",
+                s.name, s.offset
+            )),
+            (None, Some(r)) => mismatches.push(format!(
+                "'{}' at offset {} is not in the shader",
+                r.name, r.offset
+            )),
+            (None, None) => {}
+        }
+    }
+
+    if mismatches.is_empty() {
+        Ok(())
+    } else {
+        Err(UniformLayoutError::Mismatch(
+            shader.to_string(),
+            std::any::type_name::<T>().to_string(),
+            mismatches,
+        ))
+    }
+}
+
+const SPIRV_MAGIC: u32 = 0x0723_0203;
+const HEADER_WORDS: usize = 5;
+
+// opcodes
+const OP_MEMBER_NAME: u32 = 6;
+const OP_TYPE_INT: u32 = 21;
+const OP_TYPE_FLOAT: u32 = 22;
+const OP_TYPE_VECTOR: u32 = 23;
+const OP_TYPE_MATRIX: u32 = 24;
+const OP_TYPE_ARRAY: u32 = 28;
+const OP_TYPE_STRUCT: u32 = 30;
+const OP_TYPE_POINTER: u32 = 32;
+const OP_CONSTANT: u32 = 43;
+const OP_VARIABLE: u32 = 59;
+const OP_DECORATE: u32 = 71;
+const OP_MEMBER_DECORATE: u32 = 72;
+
+// decorations
+const DECORATION_ARRAY_STRIDE: u32 = 6;
+const DECORATION_MATRIX_STRIDE: u32 = 7;
+const DECORATION_BINDING: u32 = 33;
+const DECORATION_DESCRIPTOR_SET: u32 = 34;
+const DECORATION_OFFSET: u32 = 35;
+
+const STORAGE_CLASS_UNIFORM: u32 = 2;
+
+/// types of a SPIR-V module that can be part of a uniform block
+enum SpirvType {
+    Scalar(usize),
+    Vector(u32, u32),
+    Matrix(u32, u32),
+    Array(u32, u32),
+    Struct(Vec<u32>),
+    Pointer(u32, u32),
+}
+
+/// the parts of a SPIR-V module needed to reflect uniform blocks
+#[derive(Default)]
+struct SpirvModule {
+    member_names: HashMap<(u32, u32), String>,
+    decorations: HashMap<(u32, u32), u32>,
+    member_decorations: HashMap<(u32, u32, u32), u32>,
+    types: HashMap<u32, SpirvType>,
+    constants: HashMap<u32, u32>,
+    /// variables in the uniform storage class with their pointer type
+    uniforms: Vec<(u32, u32)>,
+}
+
+///
+/// Members of the uniform block at `set` and `binding` of a SPIR-V shader
+///
+/// returns [None] if the shader does not use the binding.
+/// the offsets are the ones of the `Offset` decorations, the sizes include the strides
+/// of arrays and matrices, e.g. a `float[4]` in a std140 block has a size of 64 bytes
+pub fn reflect_uniform_block(
+    spirv: &[u32],
+    set: u32,
+    binding: u32,
+) -> Result<Option<Vec<UniformMember>>, UniformLayoutError> {
+    let module = SpirvModule::parse(spirv)?;
+
+    let variable = module.uniforms.iter().find(|&&(id, _)| {
+        module.decorations.get(&(id, DECORATION_DESCRIPTOR_SET)) == Some(&set)
+            && module.decorations.get(&(id, DECORATION_BINDING)) == Some(&binding)
+    });
+    let block = match variable.map(|&(_, ty)| module.types.get(&ty)) {
+        Some(Some(&SpirvType::Pointer(_, block))) => block,
+        Some(_) => return Err(invalid("uniform variable without pointer type")),
+        None => return Ok(None),
+    };
+    let members = match module.types.get(&block) {
+        Some(SpirvType::Struct(members)) => members,
+        _ => return Err(invalid("uniform block is not a struct")),
+    };
+
+    let mut result = Vec::new();
+    for (index, &member) in members.iter().enumerate() {
+        let index = index as u32;
+        let name = module
+            .member_names
+            .get(&(block, index))
+            .cloned()
+            .unwrap_or_else(|| format!("member {}", index));
+        let offset = module
+            .member_decorations
+            .get(&(block, index, DECORATION_OFFSET))
+            .copied()
+            .ok_or_else(|| invalid(&format!("'{}' has no offset", name)))?;
+        let matrix_stride = module
+            .member_decorations
+            .get(&(block, index, DECORATION_MATRIX_STRIDE))
+            .copied();
+        result.push(UniformMember {
+            size: module.size_of(member, matrix_stride)?,
+            name,
+            offset: offset as usize,
+        });
+    }
+    Ok(Some(result))
+}
+
+impl SpirvModule {
+    fn parse(spirv: &[u32]) -> Result<Self, UniformLayoutError> {
+        if spirv.len() < HEADER_WORDS || spirv[0] != SPIRV_MAGIC {
+            return Err(invalid("missing header"));
+        }
+
+        let mut module = Self::default();
+        let mut words = &spirv[HEADER_WORDS..];
+        while let Some(&first) = words.first() {
+            let count = (first >> 16) as usize;
+            if count == 0 || count > words.len() {
+                return Err(invalid("truncated instruction"));
+            }
+            let op = &words[1..count];
+            let operand = |i: usize| {
+                op.get(i).copied().ok_or_else(|| {
+                    invalid(&format!("missing operand of opcode {}", first & 0xffff))
+                })
+            };
+
+            match first & 0xffff {
+                OP_MEMBER_NAME => {
+                    let key = (operand(0)?, operand(1)?);
+                    module.member_names.insert(key, string(&op[2..]));
+                }
+                OP_DECORATE => {
+                    if let Some(&value) = op.get(2) {
+                        module.decorations.insert((operand(0)?, operand(1)?), value);
+                    }
+                }
+                OP_MEMBER_DECORATE => {
+                    if let Some(&value) = op.get(3) {
+                        let key = (operand(0)?, operand(1)?, operand(2)?);
+                        module.member_decorations.insert(key, value);
+                    }
+                }
+                OP_TYPE_INT | OP_TYPE_FLOAT => {
+                    let bytes = operand(1)? as usize / 8;
+                    module.types.insert(operand(0)?, SpirvType::Scalar(bytes));
+                }
+                OP_TYPE_VECTOR => {
+                    let ty = SpirvType::Vector(operand(1)?, operand(2)?);
+                    module.types.insert(operand(0)?, ty);
+                }
+                OP_TYPE_MATRIX => {
+                    let ty = SpirvType::Matrix(operand(1)?, operand(2)?);
+                    module.types.insert(operand(0)?, ty);
+                }
+                OP_TYPE_ARRAY => {
+                    let ty = SpirvType::Array(operand(1)?, operand(2)?);
+                    module.types.insert(operand(0)?, ty);
+                }
+                OP_TYPE_STRUCT => {
+                    let id = operand(0)?;
+                    let members = op.get(1..).unwrap_or_default().to_vec();
+                    module.types.insert(id, SpirvType::Struct(members));
+                }
+                OP_TYPE_POINTER => {
+                    let ty = SpirvType::Pointer(operand(1)?, operand(2)?);
+                    module.types.insert(operand(0)?, ty);
+                }
+                OP_CONSTANT => {
+                    module.constants.insert(operand(1)?, operand(2)?);
+                }
+                OP_VARIABLE if op.get(2) == Some(&STORAGE_CLASS_UNIFORM) => {
+                    module.uniforms.push((operand(1)?, operand(0)?));
+                }
+                _ => {}
+            }
+            words = &words[count..];
+        }
+        Ok(module)
+    }
+
+    /// size of a type in bytes, `matrix_stride` is the stride of a matrix member
+    fn size_of(&self, ty: u32, matrix_stride: Option<u32>) -> Result<usize, UniformLayoutError> {
+        let unknown = || invalid(&format!("unknown type {}", ty));
+        match self.types.get(&ty).ok_or_else(unknown)? {
+            SpirvType::Scalar(bytes) => Ok(*bytes),
+            SpirvType::Vector(component, count) => {
+                Ok(self.size_of(*component, None)? * *count as usize)
+            }
+            SpirvType::Matrix(column, count) => {
+                let stride = match matrix_stride {
+                    Some(stride) => stride as usize,
+                    None => self.size_of(*column, None)?,
+                };
+                Ok(stride * *count as usize)
+            }
+            SpirvType::Array(element, length) => {
+                let length = *self
+                    .constants
+                    .get(length)
+                    .ok_or_else(|| invalid("array length is not a constant"))?;
+                let stride = match self.decorations.get(&(ty, DECORATION_ARRAY_STRIDE)) {
+                    Some(&stride) => stride as usize,
+                    None => self.size_of(*element, matrix_stride)?,
+                };
+                Ok(stride * length as usize)
+            }
+            SpirvType::Struct(members) => {
+                // the end of the last member, the padding of the struct is not included
+                let mut end = 0;
+                for (index, &member) in members.iter().enumerate() {
+                    let decoration = |d| self.member_decorations.get(&(ty, index as u32, d));
+                    let offset = decoration(DECORATION_OFFSET).copied().unwrap_or(0) as usize;
+                    let stride = decoration(DECORATION_MATRIX_STRIDE).copied();
+                    end = end.max(offset + self.size_of(member, stride)?);
+                }
+                Ok(end)
+            }
+            SpirvType::Pointer(..) => Err(invalid("pointer inside a uniform block")),
+        }
+    }
+}
+
+/// decode a null terminated SPIR-V string literal
+fn string(words: &[u32]) -> String {
+    let bytes: Vec<u8> = words
+        .iter()
+        .flat_map(|word| word.to_le_bytes())
+        .take_while(|&b| b != 0)
+        .collect();
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+fn invalid(message: &str) -> UniformLayoutError {
+    UniformLayoutError::InvalidSpirv(message.to_string())
+}
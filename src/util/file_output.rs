@@ -0,0 +1,37 @@
+use nannou::wgpu::TextueSnapshot;
+use std::path::PathBuf;
+
+///
+/// Writes frames as numbered PNG files, e.g. `frame_000042.png`
+///
+/// the snapshots are read asynchronously, a file is written once the texture is mapped
+pub struct FileOutput {
+    directory: PathBuf,
+    frame: u64,
+}
+
+impl FileOutput {
+    /// write the frames into `directory`, it is created if it does not exist
+    pub fn new(directory: PathBuf) -> std::io::Result<Self> {
+        std::fs::create_dir_all(&directory)?;
+        Ok(Self {
+            directory,
+            frame: 0,
+        })
+    }
+
+    /// write the snapshot as the next frame
+    pub fn update_snapshot(&mut self, snapshot: TextueSnapshot) {
+        let path = self.directory.join(format!("frame_{:06}.png", self.frame));
+        self.frame += 1;
+
+        snapshot
+            .read(move |result| {
+                let image = result.expect("faild to map texture").to_owned();
+                if let Err(e) = image.save(&path) {
+                    println!("failed to write {}: {}", path.display(), e);
+                }
+            })
+            .unwrap();
+    }
+}
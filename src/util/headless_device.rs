@@ -0,0 +1,53 @@
+use wgpu::{AdapterInfo, Device, Queue};
+
+///
+/// Render device without a window or surface
+///
+/// uses a Vulkan adapter, this also works with a software driver like lavapipe.
+/// the device must be polled to finish snapshots, see [HeadlessDevice::poll]
+///
+pub struct HeadlessDevice {
+    pub device: Device,
+    pub queue: Queue,
+    /// name and type of the used adapter
+    pub info: AdapterInfo,
+}
+
+impl HeadlessDevice {
+    /// request an adapter and a device with the `features` enabled
+    ///
+    /// returns [None] if there is no adapter supporting all `features`
+    pub fn new(features: wgpu::Features) -> Option<Self> {
+        let instance = wgpu::Instance::new(wgpu::Backends::VULKAN);
+        let options = wgpu::RequestAdapterOptions {
+            power_preference: wgpu::PowerPreference::HighPerformance,
+            force_fallback_adapter: false,
+            compatible_surface: None,
+        };
+        let adapter = pollster::block_on(instance.request_adapter(&options))?;
+        if !adapter.features().contains(features) {
+            return None;
+        }
+
+        let device_desc = wgpu::DeviceDescriptor {
+            label: Some("headless"),
+            features,
+            limits: wgpu::Limits::default(),
+        };
+        let (device, queue) =
+            pollster::block_on(adapter.request_device(&device_desc, None)).ok()?;
+
+        Some(Self {
+            device,
+            queue,
+            info: adapter.get_info(),
+        })
+    }
+
+    /// process finished GPU work, e.g. the mapping of snapshots
+    ///
+    /// nannou polls the device of a window every frame, without a window this must be called instead
+    pub fn poll(&self) {
+        self.device.poll(wgpu::Maintain::Poll);
+    }
+}
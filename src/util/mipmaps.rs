@@ -0,0 +1,101 @@
+use crate::shader_target::{Shader2DTarget, ShaderTargetBuilder};
+use crate::shapes::{FULL_SCREEN_QUAD, FULL_SCREEN_QUAD_INDEX};
+use crate::util::shader::read_shader_file;
+use crate::{RenderContext, UniformLayoutError};
+use nannou::image::EncodableLayout;
+use nannou::wgpu;
+use nannou::wgpu::{CommandEncoder, Device, TextureView};
+use wgpu::ShaderModuleDescriptorSpirV;
+
+/// format of the levels, float so inputs in a float [crate::NodeFormat] keep their range
+const MIP_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+
+/// the [ShaderTarget](crate::shader_target::ShaderTarget) always binds a uniform block, the shader does not use it
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct UniformsMipmaps {
+    f0: f32,
+}
+crate::uniform_layout!(UniformsMipmaps { f0 });
+
+///
+/// Copies a texture into a texture with a full mip chain
+///
+/// used by the [crate::TextureTree] for inputs sampled with [crate::SamplerConfig] `mipmaps`.
+/// level 0 has the size of the input, every further level is rendered
+/// from the previous one at half the size using linear filtering.
+/// the texture is recreated when the size of the input changes.
+///
+/// ## shaders used
+/// - `shader/minimal2d.vert` shared simple vertex shader
+/// - `shader/resample.frag` to copy and scale down the levels
+pub struct MipChain {
+    size: [u32; 2],
+    level_count: u32,
+    target: Shader2DTarget<UniformsMipmaps>,
+}
+
+impl MipChain {
+    /// create a mip chain for inputs of the given `size`
+    pub fn new(device: &Device, size: [u32; 2]) -> Result<Self, UniformLayoutError> {
+        let level_count = 32 - size[0].max(size[1]).max(1).leading_zeros();
+
+        let vert_raw = read_shader_file("shader/minimal2d_vert.spv");
+        let frag_raw = read_shader_file("shader/resample_frag.spv");
+        let vert = ShaderModuleDescriptorSpirV {
+            label: Some("minimal2d_vert"),
+            source: nannou::wgpu::util::make_spirv_raw(vert_raw.as_bytes()),
+        };
+        let frag = ShaderModuleDescriptorSpirV {
+            label: Some("resample_frag"),
+            source: nannou::wgpu::util::make_spirv_raw(frag_raw.as_bytes()),
+        };
+
+        let target = ShaderTargetBuilder::new(
+            &vert,
+            &frag,
+            &FULL_SCREEN_QUAD,
+            &FULL_SCREEN_QUAD_INDEX,
+            UniformsMipmaps { f0: 0.0 },
+        )
+        .input_textures(1)
+        .format(MIP_FORMAT)
+        .mip_level_count(level_count)
+        .build(device, size)?;
+
+        Ok(Self {
+            size,
+            level_count,
+            target,
+        })
+    }
+
+    /// record copying `input` into level 0 and rendering the other levels into `encoder`
+    ///
+    /// returns the view of all levels, or `input` if the chain can not be recreated for a new size
+    pub fn update(
+        &mut self,
+        ctx: &RenderContext,
+        encoder: &mut CommandEncoder,
+        input: TextureView,
+    ) -> TextureView {
+        if input.size() != self.size {
+            match Self::new(ctx.device, input.size()) {
+                Ok(chain) => *self = chain,
+                Err(e) => {
+                    println!("MipChain: {}", e);
+                    return input;
+                }
+            }
+        }
+
+        self.target
+            .render_pass_to_level(ctx.device, encoder, vec![input], 0);
+        for level in 1..self.level_count {
+            let previous = self.target.level_view(level - 1);
+            self.target
+                .render_pass_to_level(ctx.device, encoder, vec![previous], level);
+        }
+        self.target.texture_view()
+    }
+}
@@ -0,0 +1,79 @@
+use crate::util::ndi_stream::NdiStream;
+use crate::{
+    OutputDescription, OutputStage, ParameterStore, RenderContext, TextureTree, UniformLayoutError,
+};
+use nannou::wgpu::{Device, TextureCapturer};
+
+///
+/// Sends an output node of a [TextureTree] as its own NDI stream
+///
+/// the node is converted by its own [OutputStage], and scaled if the output has a resolution set.
+/// frames rendered faster than the `framerate` of the output are dropped
+pub struct NdiOutput {
+    description: OutputDescription,
+    stream: NdiStream,
+    stage: OutputStage,
+}
+
+impl NdiOutput {
+    /// create the NDI sender described by `description`
+    ///
+    /// the stage uses the `/output/color/*` parameters of the program output
+    pub fn new(
+        description: OutputDescription,
+        device: &Device,
+        store: &mut ParameterStore,
+    ) -> Result<Self, UniformLayoutError> {
+        // without a resolution the stage gets the size of the node with the first frame
+        let stage = OutputStage::new(device, description.resolution.unwrap_or([1, 1]), store)?;
+        let stream = NdiStream::new(description.name.clone(), description.framerate);
+
+        Ok(Self {
+            description,
+            stream,
+            stage,
+        })
+    }
+
+    /// the description this output was created from
+    pub fn description(&self) -> &OutputDescription {
+        &self.description
+    }
+
+    /// send the current frame of the output node, if a frame is due
+    ///
+    /// must be called after the tree was updated
+    pub fn update(
+        &mut self,
+        tree: &TextureTree,
+        ctx: &RenderContext,
+        store: &ParameterStore,
+        texture_capturer: &TextureCapturer,
+        timecode: i64,
+    ) {
+        if !self.stream.frame_due() {
+            // the frame queued last time is ready by now
+            self.stream.send_video_from_queue();
+            return;
+        }
+        let node = match tree.find(&self.description.node) {
+            Some(node) => node,
+            None => return,
+        };
+
+        let texture = tree.node_output(node);
+        let size = self
+            .description
+            .resolution
+            .unwrap_or_else(|| texture.size());
+        self.stage.convert(ctx, store, texture, size);
+
+        let snapshot = self.stage.snapshot(ctx, texture_capturer);
+        self.stream.update_snapshot(snapshot, timecode);
+    }
+
+    /// send the last queued frame
+    pub fn send_video_from_queue(&mut self) {
+        self.stream.send_video_from_queue();
+    }
+}
@@ -1,4 +1,5 @@
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use crossbeam::queue::ArrayQueue;
 use image::{ImageBuffer, Rgba};
@@ -8,25 +9,54 @@ type NdiImageBuffer = ImageBuffer<Rgba<u8>, Vec<u8>>;
 type NdiTimedFrame = (Box<NdiImageBuffer>, i64);
 
 /// NDI Video Stream
+///
+/// sends at most `framerate` frames per second, see [NdiStream::frame_due]
 pub struct NdiStream {
     send: ndi::Send,
     framerate: i32,
     queue: Arc<ArrayQueue<NdiTimedFrame>>,
     frame: Option<ndi::VideoData>,
+    /// time the next frame is due
+    next_frame: Option<Instant>,
 }
 
 impl NdiStream {
     /// create new NdiStream
+    ///
+    /// the frames are paced by [NdiStream::frame_due], the sender does not clock them,
+    /// so sending does not block the render loop
     pub fn new(name: String, framerate: i32) -> Self {
         Self {
             send: ndi::SendBuilder::new()
                 .ndi_name(name)
-                .clock_video(true)
+                .clock_video(false)
                 .build()
                 .expect("error creating NDI sender"),
-            framerate,
+            framerate: framerate.max(1),
             queue: Arc::new(ArrayQueue::new(2)),
             frame: None,
+            next_frame: None,
+        }
+    }
+
+    /// true if the current frame should be sent to keep the framerate of the stream
+    ///
+    /// the other rendered frames are dropped. call once per rendered frame
+    pub fn frame_due(&mut self) -> bool {
+        let now = Instant::now();
+        let interval = Duration::from_secs_f64(1.0 / self.framerate as f64);
+        match self.next_frame {
+            // half an interval early is still on time, the render loop jitters
+            Some(next) if now + interval / 2 < next => false,
+            Some(next) if now < next + interval => {
+                self.next_frame = Some(next + interval);
+                true
+            }
+            // first frame, or the renderer fell behind
+            _ => {
+                self.next_frame = Some(now + interval);
+                true
+            }
         }
     }
 
@@ -0,0 +1,94 @@
+use crate::shader_target::{Shader2DTarget, ShaderTargetBuilder};
+use crate::shapes::{FULL_SCREEN_QUAD, FULL_SCREEN_QUAD_INDEX};
+use crate::util::shader::read_shader_file;
+use crate::{RenderContext, SamplerConfig, UniformLayoutError};
+use nannou::image::EncodableLayout;
+use nannou::wgpu::{
+    CommandEncoder, Device, TextueSnapshot, TextureCapturer, TextureFormat, TextureView,
+};
+use wgpu::ShaderModuleDescriptorSpirV;
+
+/// the [ShaderTarget](crate::shader_target::ShaderTarget) always binds a uniform block, the shader does not use it
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct UniformsResample {
+    f0: f32,
+}
+crate::uniform_layout!(UniformsResample { f0 });
+
+///
+/// Scales a texture to another resolution
+///
+/// ## shaders used
+/// - `shader/minimal2d.vert` shared simple vertex shader
+/// - `shader/resample.frag` shader for this
+pub struct Resampler {
+    target: Shader2DTarget<UniformsResample>,
+}
+
+impl Resampler {
+    /// create a resampler with the output resolution `size`
+    ///
+    /// the input is converted to `format`, e.g. [crate::OUTPUT_FORMAT] before it is sent
+    pub fn new(
+        device: &Device,
+        size: [u32; 2],
+        format: TextureFormat,
+    ) -> Result<Self, UniformLayoutError> {
+        let vert_raw = read_shader_file("shader/minimal2d_vert.spv");
+        let frag_raw = read_shader_file("shader/resample_frag.spv");
+
+        let vert_data = nannou::wgpu::util::make_spirv_raw(vert_raw.as_bytes());
+        let frag_data = nannou::wgpu::util::make_spirv_raw(frag_raw.as_bytes());
+
+        let vert = ShaderModuleDescriptorSpirV {
+            label: Some("minimal2d_vert"),
+            source: vert_data,
+        };
+
+        let frag = ShaderModuleDescriptorSpirV {
+            label: Some("resample_frag"),
+            source: frag_data,
+        };
+
+        let target = ShaderTargetBuilder::new(
+            &vert,
+            &frag,
+            &FULL_SCREEN_QUAD,
+            &FULL_SCREEN_QUAD_INDEX,
+            UniformsResample { f0: 0.0 },
+        )
+        .input_textures(1)
+        .format(format)
+        .build(device, size)?;
+
+        Ok(Self { target })
+    }
+
+    /// record rendering `input` scaled to the output resolution into `encoder`
+    pub fn update(
+        &mut self,
+        ctx: &RenderContext,
+        encoder: &mut CommandEncoder,
+        input: TextureView,
+    ) {
+        self.target.render_pass(ctx.device, encoder, vec![input]);
+    }
+
+    /// change how the input is sampled, see [SamplerConfig]
+    pub fn set_sampler(&mut self, device: &Device, sampler: SamplerConfig) {
+        self.target.set_samplers(device, &[sampler]);
+    }
+
+    pub fn output(&self) -> TextureView {
+        self.target.texture_view()
+    }
+
+    pub fn snapshot(
+        &self,
+        ctx: &RenderContext,
+        texture_capturer: &TextureCapturer,
+    ) -> TextueSnapshot {
+        self.target.snapshot(ctx, texture_capturer)
+    }
+}
@@ -0,0 +1,56 @@
+use nannou_osc::{Message, Type};
+use visgen_graph::{Clock, ParameterStore};
+
+fn set(store: &mut ParameterStore, addr: &str, value: Type) {
+    store.update(&Message {
+        addr: addr.to_string(),
+        args: Some(vec![value]),
+    });
+}
+
+#[test]
+fn test_clock_fixed_step() {
+    let mut store = ParameterStore::new();
+    let mut clock = Clock::with_fixed_fps(&mut store, 50.0);
+
+    // the real time is not used with a fixed step
+    clock.tick(&store, 1.0);
+    assert_eq!(clock.frame(), 0);
+    assert!((clock.time() - 0.02).abs() < 1e-6);
+
+    set(&mut store, "/clock/speed", Type::Float(2.0));
+    clock.tick(&store, 1.0);
+    assert_eq!(clock.frame(), 1);
+    assert!((clock.delta_time() - 0.04).abs() < 1e-6);
+    assert!((clock.time() - 0.06).abs() < 1e-6);
+
+    set(&mut store, "/clock/pause", Type::Bool(true));
+    clock.tick(&store, 1.0);
+    assert_eq!(clock.delta_time(), 0.0);
+    assert!((clock.time() - 0.06).abs() < 1e-6);
+}
+
+#[test]
+fn test_clock_seek_and_beat() {
+    let mut store = ParameterStore::new();
+    let mut clock = Clock::new(&mut store);
+
+    // 120 bpm, a beat every 0.5 seconds
+    clock.tick(&store, 0.25);
+    assert!((clock.beat_phase() - 0.5).abs() < 1e-6);
+
+    clock.update_osc(&Message {
+        addr: "/clock/seek".to_string(),
+        args: Some(vec![Type::Float(10.0)]),
+    });
+    clock.tick(&store, 0.1);
+    assert!((clock.time() - 10.1).abs() < 1e-5);
+
+    // seeking backwards moves the time back, nodes see a negative delta
+    clock.seek(1.0);
+    clock.tick(&store, 0.0);
+    assert_eq!(clock.time(), 1.0);
+    assert!(clock.delta_time() < 0.0);
+    // the stream timecode follows the show time
+    assert_eq!(clock.timecode(), 10000);
+}
@@ -0,0 +1,232 @@
+use nannou::wgpu::Features;
+use visgen_graph::{GraphDescription, GraphError, NodeFormat, SamplerAddress, SamplerFilter};
+
+fn parse(json: &str) -> GraphDescription {
+    serde_json::from_str(json).unwrap()
+}
+
+#[test]
+fn test_parse_graph_description() {
+    let graph = parse(
+        r#"{
+            "texture_size": [512, 512],
+            "root": "fader",
+            "nodes": [
+                { "name": "wave", "type": "wave" },
+                { "name": "stripes", "type": "stripes", "size": [256, 256] },
+                { "name": "fader", "type": "fader", "inputs": ["wave", "stripes"] }
+            ]
+        }"#,
+    );
+
+    assert_eq!(graph.texture_size, [512, 512]);
+    assert_eq!(graph.nodes.len(), 3);
+    assert_eq!(graph.nodes[1].size, Some([256, 256]));
+    assert_eq!(graph.nodes[2].inputs, vec!["wave", "stripes"]);
+    assert!(graph.validate().is_ok());
+
+    // store and load again gives the same graph
+    let json = serde_json::to_string(&graph).unwrap();
+    assert_eq!(parse(&json), graph);
+}
+
+#[test]
+fn test_validate_graph_description() {
+    let unknown_input = parse(
+        r#"{ "texture_size": [1, 1], "root": "a",
+             "nodes": [ { "name": "a", "type": "fader", "inputs": ["b"] } ] }"#,
+    );
+    assert!(matches!(
+        unknown_input.validate(),
+        Err(GraphError::UnknownNode(n)) if n == "b"
+    ));
+
+    let duplicate = parse(
+        r#"{ "texture_size": [1, 1], "root": "a",
+             "nodes": [ { "name": "a", "type": "wave" }, { "name": "a", "type": "wave" } ] }"#,
+    );
+    assert!(matches!(
+        duplicate.validate(),
+        Err(GraphError::DuplicateNode(_))
+    ));
+
+    let unknown_root = parse(
+        r#"{ "texture_size": [1, 1], "root": "b",
+             "nodes": [ { "name": "a", "type": "wave" } ] }"#,
+    );
+    assert!(matches!(
+        unknown_root.validate(),
+        Err(GraphError::UnknownNode(n)) if n == "b"
+    ));
+
+    let unknown_feedback = parse(
+        r#"{ "texture_size": [1, 1], "root": "a",
+             "nodes": [ { "name": "a", "type": "feedback", "feedback": "b" } ] }"#,
+    );
+    assert!(matches!(
+        unknown_feedback.validate(),
+        Err(GraphError::UnknownNode(n)) if n == "b"
+    ));
+}
+
+#[test]
+fn test_graph_outputs() {
+    let graph = parse(
+        r#"{ "texture_size": [1, 1], "root": "a",
+             "nodes": [ { "name": "a", "type": "wave" }, { "name": "b", "type": "wave" } ],
+             "outputs": [ { "name": "second", "node": "b", "resolution": [1920, 1080] } ] }"#,
+    );
+    assert_eq!(graph.outputs[0].framerate, 60);
+    assert_eq!(graph.outputs[0].resolution, Some([1920, 1080]));
+    assert!(graph.validate().is_ok());
+
+    let unknown_node = parse(
+        r#"{ "texture_size": [1, 1], "root": "a",
+             "nodes": [ { "name": "a", "type": "wave" } ],
+             "outputs": [ { "name": "second", "node": "b" } ] }"#,
+    );
+    assert!(matches!(
+        unknown_node.validate(),
+        Err(GraphError::UnknownNode(n)) if n == "b"
+    ));
+
+    let duplicate = parse(
+        r#"{ "texture_size": [1, 1], "root": "a",
+             "nodes": [ { "name": "a", "type": "wave" } ],
+             "outputs": [ { "name": "x", "node": "a" }, { "name": "x", "node": "a" } ] }"#,
+    );
+    assert!(matches!(
+        duplicate.validate(),
+        Err(GraphError::DuplicateOutput(_))
+    ));
+
+    // the root stream is declared by the graph, without it the default is used
+    assert_eq!(graph.stream.name, "visgen_graph");
+    assert_eq!(graph.stream.framerate, 60);
+    let stream = parse(
+        r#"{ "texture_size": [1, 1], "root": "a",
+             "nodes": [ { "name": "a", "type": "wave" } ],
+             "stream": { "name": "program", "framerate": 0 } }"#,
+    );
+    assert_eq!(stream.stream.name, "program");
+    assert!(matches!(
+        stream.validate(),
+        Err(GraphError::InvalidFramerate(n)) if n == "program"
+    ));
+}
+
+#[test]
+fn test_node_size() {
+    let graph = parse(
+        r#"{ "texture_size": [1920, 1080], "root": "a",
+             "nodes": [ { "name": "a", "type": "wave" },
+                        { "name": "b", "type": "clouds", "scale": 0.25 },
+                        { "name": "c", "type": "wave", "size": [64, 32] } ] }"#,
+    );
+    assert!(graph.validate().is_ok());
+    assert_eq!(graph.nodes[0].output_size(graph.texture_size), [1920, 1080]);
+    assert_eq!(graph.nodes[1].output_size(graph.texture_size), [480, 270]);
+    assert_eq!(graph.nodes[2].output_size(graph.texture_size), [64, 32]);
+
+    let both = parse(
+        r#"{ "texture_size": [1, 1], "root": "a",
+             "nodes": [ { "name": "a", "type": "wave", "size": [2, 2], "scale": 0.5 } ] }"#,
+    );
+    assert!(matches!(both.validate(), Err(GraphError::InvalidSize(_))));
+}
+
+#[test]
+fn test_node_format() {
+    let graph = parse(
+        r#"{ "texture_size": [1, 1], "root": "a", "format": "rgba16float",
+             "nodes": [ { "name": "a", "type": "wave" },
+                        { "name": "b", "type": "wave", "format": "rgba8" } ] }"#,
+    );
+    assert_eq!(graph.format, NodeFormat::Rgba16Float);
+    assert_eq!(graph.nodes[0].format, None);
+    assert_eq!(graph.nodes[1].format, Some(NodeFormat::Rgba8));
+    // border samplers are always requested
+    assert_eq!(
+        graph.required_features(),
+        Features::ADDRESS_MODE_CLAMP_TO_BORDER
+    );
+
+    // the default format is not stored
+    let json = serde_json::to_string(&parse(
+        r#"{ "texture_size": [1, 1], "root": "a", "nodes": [ { "name": "a", "type": "wave" } ] }"#,
+    ))
+    .unwrap();
+    assert!(!json.contains("format"));
+
+    let float32 = parse(
+        r#"{ "texture_size": [1, 1], "root": "a", "nodes": [ { "name": "a", "type": "g" } ],
+             "groups": { "g": { "root": "b",
+                 "nodes": [ { "name": "b", "type": "wave", "format": "rgba32float" } ] } } }"#,
+    );
+    assert_eq!(
+        float32.required_features(),
+        Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES | Features::ADDRESS_MODE_CLAMP_TO_BORDER
+    );
+}
+
+#[test]
+fn test_groups() {
+    let graph = parse(
+        r#"{ "texture_size": [1, 1], "root": "fog1",
+             "nodes": [ { "name": "wave", "type": "wave" },
+                        { "name": "fog1", "type": "fog", "inputs": ["wave"] },
+                        { "name": "fog2", "type": "fog" } ],
+             "groups": { "fog": {
+                 "inputs": ["background"],
+                 "root": "mask",
+                 "nodes": [ { "name": "perlin", "type": "perlin" },
+                            { "name": "mask", "type": "mask", "inputs": ["background", "perlin"] } ],
+                 "parameters": { "density": "perlin/scalex" } } } }"#,
+    );
+    assert!(graph.validate().is_ok());
+    assert_eq!(graph.groups["fog"].parameters["density"], "perlin/scalex");
+
+    let unknown_input = parse(
+        r#"{ "texture_size": [1, 1], "root": "a", "nodes": [ { "name": "a", "type": "wave" } ],
+             "groups": { "g": { "root": "b",
+                 "nodes": [ { "name": "b", "type": "mask", "inputs": ["missing"] } ] } } }"#,
+    );
+    assert!(matches!(
+        unknown_input.validate(),
+        Err(GraphError::UnknownNode(n)) if n == "missing"
+    ));
+
+    let recursive = parse(
+        r#"{ "texture_size": [1, 1], "root": "a", "nodes": [ { "name": "a", "type": "wave" } ],
+             "groups": { "g": { "root": "b", "nodes": [ { "name": "b", "type": "h" } ] },
+                         "h": { "root": "c", "nodes": [ { "name": "c", "type": "g" } ] } } }"#,
+    );
+    assert!(matches!(recursive.validate(), Err(GraphError::Cycle(_))));
+}
+
+#[test]
+fn test_samplers() {
+    let graph = parse(
+        r#"{ "texture_size": [1, 1], "root": "b",
+             "nodes": [ { "name": "a", "type": "wave" },
+                        { "name": "b", "type": "color_ramp", "inputs": ["a"],
+                          "samplers": { "input": { "address": "mirror", "mipmaps": true } } } ] }"#,
+    );
+    assert!(graph.nodes[0].samplers.is_empty());
+    let sampler = graph.nodes[1].samplers["input"];
+    assert_eq!(sampler.address, SamplerAddress::Mirror);
+    assert_eq!(sampler.filter, SamplerFilter::Linear);
+    assert!(sampler.mipmaps);
+
+    // nodes without samplers do not store them
+    let json = serde_json::to_string(&graph).unwrap();
+    assert_eq!(json.matches("samplers").count(), 1);
+    assert_eq!(parse(&json), graph);
+
+    let invalid = serde_json::from_str::<GraphDescription>(
+        r#"{ "texture_size": [1, 1], "root": "a",
+             "nodes": [ { "name": "a", "type": "wave",
+                          "samplers": { "input": { "address": "wrap" } } } ] }"#,
+    );
+    assert!(invalid.is_err());
+}
@@ -1,6 +1,7 @@
 use nannou_osc::{Message, Type};
 use visgen_graph::{
     ParameterEnd, ParameterEndpoint, ParameterFactory, ParameterHandle, ParameterStore,
+    PhaseAccumulator,
 };
 
 #[test]
@@ -66,3 +67,90 @@ fn test_integration_parameters() {
     msg.addr = "group_b/c".to_string();
     store.update(&msg);
 }
+
+#[test]
+fn test_unregister_parameters() {
+    let mut store = ParameterStore::new();
+    let freq: ParameterEndpoint<f32> =
+        ParameterEndpoint::new(1.0, "/wave/freq".to_string(), &mut store);
+    let _other: ParameterEndpoint<f32> =
+        ParameterEndpoint::new(2.0, "/wave2/freq".to_string(), &mut store);
+
+    assert_eq!(store.unregister("/wave"), 1);
+    assert!(store.get_path_value("/wave/freq").is_none());
+    assert!(store.get_path_value("/wave2/freq").is_some());
+
+    // removed parameters are no longer updated by messages
+    store.update(&Message {
+        addr: "/wave/freq".to_string(),
+        args: Some(vec![Type::Float(5.0)]),
+    });
+    assert_eq!(freq.get(&store), 1.0);
+
+    // the address can be used again
+    let again: ParameterEndpoint<f32> =
+        ParameterEndpoint::new(3.0, "/wave/freq".to_string(), &mut store);
+    assert_eq!(again.get(&store), 3.0);
+    assert_eq!(store.to_string().lines().count(), 2);
+}
+
+#[test]
+fn test_changed_since() {
+    let mut store = ParameterStore::new();
+    let _freq: ParameterEndpoint<f32> =
+        ParameterEndpoint::new(1.0, "/wave/freq".to_string(), &mut store);
+    let _other: ParameterEndpoint<f32> =
+        ParameterEndpoint::new(2.0, "/wave2/freq".to_string(), &mut store);
+    let revision = store.revision();
+    assert!(!store.changed_since("/wave", revision));
+
+    // the same value is no change
+    let msg = |value| Message {
+        addr: "/wave2/freq".to_string(),
+        args: Some(vec![Type::Float(value)]),
+    };
+    store.update(&msg(2.0));
+    assert_eq!(store.revision(), revision);
+
+    store.update(&msg(3.0));
+    assert!(store.changed_since("/wave2", revision));
+    assert!(!store.changed_since("/wave", revision));
+}
+
+#[test]
+fn test_parameter_alias() {
+    let mut store = ParameterStore::new();
+    let sx: ParameterEndpoint<f32> =
+        ParameterEndpoint::new(1.0, "/fog1/perlin/scalex".to_string(), &mut store);
+
+    assert!(store.add_alias("/fog1/density", "/fog1/perlin/scalex"));
+    assert!(!store.add_alias("/fog1/density", "/fog1/perlin/scalex"));
+    assert!(!store.add_alias("/fog1/other", "/fog1/perlin/unknown"));
+
+    // both addresses update the same value
+    store.update(&Message {
+        addr: "/fog1/density".to_string(),
+        args: Some(vec![Type::Float(4.0)]),
+    });
+    assert_eq!(sx.get(&store), 4.0);
+    assert_eq!(store.to_string().lines().count(), 1);
+
+    assert_eq!(store.unregister("/fog1"), 2);
+}
+
+#[test]
+fn test_phase_accumulator() {
+    let mut store = ParameterStore::new();
+    let speed = ParameterEndpoint::new(2.0, "/wave/speed".to_string(), &mut store);
+    let mut phase = PhaseAccumulator::new(speed);
+
+    assert_eq!(phase.advance(&store, 0.5), 1.0);
+
+    // changing the speed keeps the position, only the next frames move slower
+    store.update(&Message {
+        addr: "/wave/speed".to_string(),
+        args: Some(vec![Type::Float(0.5)]),
+    });
+    assert_eq!(phase.get(), 1.0);
+    assert_eq!(phase.advance(&store, 0.5), 1.25);
+}
@@ -0,0 +1,49 @@
+use visgen_graph::{NodeStats, RollingAverage, TreeStats};
+
+#[test]
+fn test_rolling_average() {
+    let mut average = RollingAverage::default();
+    assert_eq!(average.get(), 0.0);
+
+    average.add(10.0);
+    assert_eq!(average.get(), 10.0);
+
+    // new samples only move the average slowly
+    average.add(20.0);
+    assert!(average.get() > 10.0 && average.get() < 15.0);
+}
+
+#[test]
+fn test_stats_messages() {
+    let mut stats = TreeStats {
+        fps: 60.0,
+        frame_ms: 16.6,
+        ..Default::default()
+    };
+    stats.nodes.insert(
+        "wave".to_string(),
+        NodeStats {
+            cpu_ms: 0.5,
+            gpu_ms: None,
+        },
+    );
+    stats.nodes.insert(
+        "fader".to_string(),
+        NodeStats {
+            cpu_ms: 0.2,
+            gpu_ms: Some(1.0),
+        },
+    );
+
+    let addrs: Vec<String> = stats.messages().into_iter().map(|m| m.addr).collect();
+    assert_eq!(
+        addrs,
+        vec![
+            "/stats/fps",
+            "/stats/frame_ms",
+            "/stats/fader/cpu_ms",
+            "/stats/fader/gpu_ms",
+            "/stats/wave/cpu_ms",
+        ]
+    );
+}
@@ -0,0 +1,448 @@
+use indextree::{Arena, NodeId};
+use nannou::wgpu::{
+    CommandEncoder, Device, Features, TextueSnapshot, TextureCapturer, TextureView,
+};
+use nannou_osc::{Message, Type};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use visgen_graph::util::headless_device::HeadlessDevice;
+use visgen_graph::{
+    Fallback, GraphDescription, GraphError, InputPort, NodeFormat, NodeInfo, NodeOptions,
+    ParameterEnd, ParameterEndpoint, ParameterStore, RenderContext, SamplerAddress, SamplerConfig,
+    TextureNode, TextureTree,
+};
+
+/// node without output, only used to build graphs. `output` and `snapshot` need a device
+///
+/// accepts any number of inputs, input `i` contributes while `weights[i]` is not 0.
+/// a `cached` node is not time dependent
+#[derive(Default)]
+struct DummyNode {
+    weights: Vec<ParameterEndpoint<f32>>,
+    cached: bool,
+}
+
+/// node without output, needs 1 input and accepts a second one
+struct RequiredNode;
+
+/// node without inputs and output that has a feedback port, keeps the samplers it gets
+struct FeedbackPortNode(Rc<RefCell<Vec<SamplerConfig>>>);
+
+impl TextureNode for DummyNode {
+    fn update(
+        &mut self,
+        _ctx: &RenderContext,
+        _encoder: &mut CommandEncoder,
+        _store: &ParameterStore,
+        _i: Vec<TextureView>,
+    ) {
+    }
+
+    fn output(&self) -> TextureView {
+        unimplemented!()
+    }
+
+    fn snapshot(&self, _ctx: &RenderContext, _capturer: &TextureCapturer) -> TextueSnapshot {
+        unimplemented!()
+    }
+
+    fn input_ports(&self) -> Vec<InputPort> {
+        vec![InputPort::optional("inputs", Fallback::Black).count(usize::MAX)]
+    }
+
+    fn input_contributes(&self, store: &ParameterStore, slot: usize) -> bool {
+        !matches!(self.weights.get(slot), Some(w) if w.get(store) == 0.0)
+    }
+
+    fn time_dependent(&self, _store: &ParameterStore) -> bool {
+        !self.cached
+    }
+}
+
+impl TextureNode for RequiredNode {
+    fn update(
+        &mut self,
+        _ctx: &RenderContext,
+        _encoder: &mut CommandEncoder,
+        _store: &ParameterStore,
+        _i: Vec<TextureView>,
+    ) {
+    }
+
+    fn output(&self) -> TextureView {
+        unimplemented!()
+    }
+
+    fn snapshot(&self, _ctx: &RenderContext, _capturer: &TextureCapturer) -> TextueSnapshot {
+        unimplemented!()
+    }
+
+    fn input_ports(&self) -> Vec<InputPort> {
+        vec![
+            InputPort::required("a"),
+            InputPort::optional("b", Fallback::White),
+        ]
+    }
+}
+
+impl TextureNode for FeedbackPortNode {
+    fn update(
+        &mut self,
+        _ctx: &RenderContext,
+        _encoder: &mut CommandEncoder,
+        _store: &ParameterStore,
+        _i: Vec<TextureView>,
+    ) {
+    }
+
+    fn output(&self) -> TextureView {
+        unimplemented!()
+    }
+
+    fn snapshot(&self, _ctx: &RenderContext, _capturer: &TextureCapturer) -> TextueSnapshot {
+        unimplemented!()
+    }
+
+    fn feedback_port(&self) -> Option<InputPort> {
+        Some(InputPort::optional("feedback", Fallback::Black))
+    }
+
+    fn set_samplers(&mut self, _device: &Device, samplers: &[SamplerConfig]) {
+        *self.0.borrow_mut() = samplers.to_vec();
+    }
+}
+
+#[test]
+fn test_shared_input_is_ordered_once() {
+    let mut arena: Arena<Box<dyn TextureNode>> = Arena::new();
+    let noise = arena.new_node(Box::new(DummyNode::default()));
+    let ramp = arena.new_node(Box::new(DummyNode::default()));
+    let mask = arena.new_node(Box::new(DummyNode::default()));
+
+    let mut inputs = HashMap::new();
+    inputs.insert(ramp, vec![noise]);
+    inputs.insert(mask, vec![ramp, noise, noise]);
+
+    let tree = TextureTree::from_edges(arena, inputs, mask, HashMap::new()).unwrap();
+    let order: Vec<_> = tree.nodes().collect();
+
+    // the shared input appears once in the update order, before all nodes using it
+    assert_eq!(order, vec![noise, ramp, mask]);
+    assert_eq!(tree.inputs(mask), vec![ramp, noise, noise]);
+}
+
+#[test]
+fn test_cycle_is_detected() {
+    let mut arena: Arena<Box<dyn TextureNode>> = Arena::new();
+    let a = arena.new_node(Box::new(DummyNode::default()));
+    let b = arena.new_node(Box::new(DummyNode::default()));
+    let c = arena.new_node(Box::new(DummyNode::default()));
+
+    let mut inputs = HashMap::new();
+    inputs.insert(a, vec![b]);
+    inputs.insert(b, vec![c]);
+    inputs.insert(c, vec![a]);
+
+    let result = TextureTree::from_edges(arena, inputs, a, HashMap::new());
+    assert!(matches!(result, Err(GraphError::Cycle(_))));
+
+    // a cycle among nodes that are not rendered is also rejected
+    let mut arena: Arena<Box<dyn TextureNode>> = Arena::new();
+    let root = arena.new_node(Box::new(DummyNode::default()));
+    let b = arena.new_node(Box::new(DummyNode::default()));
+    let c = arena.new_node(Box::new(DummyNode::default()));
+
+    let mut inputs = HashMap::new();
+    inputs.insert(b, vec![c]);
+    inputs.insert(c, vec![b]);
+    let result = TextureTree::from_edges(arena, inputs, root, HashMap::new());
+    assert!(matches!(result, Err(GraphError::Cycle(_))));
+
+    let mut arena: Arena<Box<dyn TextureNode>> = Arena::new();
+    let root = arena.new_node(Box::new(DummyNode::default()));
+    let b = arena.new_node(Box::new(DummyNode::default()));
+    let c = arena.new_node(Box::new(DummyNode::default()));
+
+    let mut inputs = HashMap::new();
+    inputs.insert(b, vec![c]);
+    let mut tree = TextureTree::from_edges(arena, inputs, root, HashMap::new()).unwrap();
+    assert!(matches!(tree.connect(c, b, 0), Err(GraphError::Cycle(_))));
+    assert!(tree.inputs(c).is_empty());
+}
+
+#[test]
+fn test_input_ports_are_validated() {
+    let mut arena: Arena<Box<dyn TextureNode>> = Arena::new();
+    let b = arena.new_node(Box::new(RequiredNode));
+    let result = TextureTree::from_edges(arena, HashMap::new(), b, HashMap::new());
+    assert!(matches!(result, Err(GraphError::MissingInput(_, port)) if port == "a"));
+
+    // the arena tree is validated the same way
+    let mut arena: Arena<Box<dyn TextureNode>> = Arena::new();
+    let b1 = arena.new_node(Box::new(RequiredNode));
+    let result = TextureTree::new(arena, b1);
+    assert!(matches!(result, Err(GraphError::MissingInput(_, port)) if port == "a"));
+
+    let mut arena: Arena<Box<dyn TextureNode>> = Arena::new();
+    let a2 = arena.new_node(Box::new(DummyNode::default()));
+    let b2 = arena.new_node(Box::new(RequiredNode));
+    let mut inputs = HashMap::new();
+    inputs.insert(b2, vec![a2, a2, a2]);
+    let result = TextureTree::from_edges(arena, inputs, b2, HashMap::new());
+    assert!(matches!(result, Err(GraphError::TooManyInputs(_, 3, 2))));
+
+    let mut arena: Arena<Box<dyn TextureNode>> = Arena::new();
+    let a3 = arena.new_node(Box::new(DummyNode::default()));
+    let b3 = arena.new_node(Box::new(RequiredNode));
+    let mut inputs = HashMap::new();
+    inputs.insert(b3, vec![a3]);
+    let mut tree = TextureTree::from_edges(arena, inputs, b3, HashMap::new()).unwrap();
+
+    // removing the required input is reverted
+    assert!(tree.disconnect(b3, 0).is_err());
+    assert!(tree.remove_node(a3).is_err());
+    assert_eq!(tree.inputs(b3), vec![a3]);
+}
+
+#[test]
+fn test_dot_export() {
+    let mut arena: Arena<Box<dyn TextureNode>> = Arena::new();
+    let noise = arena.new_node(Box::new(DummyNode::default()));
+    let mask = arena.new_node(Box::new(RequiredNode));
+
+    let info = |name: &str, node_type: &str| NodeInfo {
+        name: name.to_string(),
+        node_type: node_type.to_string(),
+        size: [1, 1],
+        format: NodeFormat::default(),
+        options: NodeOptions::new(),
+    };
+    let mut infos = HashMap::new();
+    infos.insert(noise, info("noise", "perlin"));
+    infos.insert(mask, info("mask", "mask"));
+
+    let mut inputs = HashMap::new();
+    inputs.insert(mask, vec![noise, noise]);
+    let mut tree = TextureTree::from_edges(arena, inputs, mask, infos).unwrap();
+
+    let mut store = ParameterStore::new();
+    let _speed = ParameterEndpoint::<f32>::new(0.5, "/noise/speed".to_string(), &mut store);
+
+    let dot = tree.to_dot(&store);
+    assert!(dot.starts_with("digraph visgen_graph {"));
+    assert!(dot.contains(r#""mask" [label="mask: mask (root)\l", peripheries=2];"#));
+    assert!(dot.contains(r#"noise: perlin\l\lspeed = 0.5\l"#));
+    assert!(dot.contains(r#""noise" -> "mask" [label="0: a"];"#));
+    assert!(dot.contains(r#""noise" -> "mask" [label="1: b"];"#));
+    assert!(!dot.contains("style=dotted"));
+
+    // nodes that are not rendered are dotted
+    let unused = tree
+        .add_node(Box::new(DummyNode::default()), info("unused", "wave"))
+        .unwrap();
+    tree.connect(unused, noise, 0).unwrap();
+    let dot = tree.to_dot(&store);
+    assert!(dot.contains(r#""unused" [label="unused: wave\l", style=dotted];"#));
+    assert!(dot.contains(r#""noise" -> "unused" [label="0: inputs"];"#));
+}
+
+#[test]
+fn test_feedback_links() {
+    let mut arena: Arena<Box<dyn TextureNode>> = Arena::new();
+    let wave = arena.new_node(Box::new(DummyNode::default()));
+    let trails = arena.new_node(Box::new(DummyNode::default()));
+    let fader = arena.new_node(Box::new(DummyNode::default()));
+
+    let mut inputs = HashMap::new();
+    inputs.insert(fader, vec![wave, trails]);
+    let mut tree = TextureTree::from_edges(arena, inputs, fader, HashMap::new()).unwrap();
+
+    // the source can be downstream, it is not an edge of the graph
+    tree.set_feedback(trails, Some(fader)).unwrap();
+    assert_eq!(tree.feedback_source(trails), Some(fader));
+    assert!(tree.to_dot(&ParameterStore::new()).contains("style=dashed"));
+
+    tree.set_feedback(trails, None).unwrap();
+    assert_eq!(tree.feedback_source(trails), None);
+
+    // removing the source removes the link
+    tree.set_feedback(fader, Some(wave)).unwrap();
+    tree.set_feedback(trails, Some(wave)).unwrap();
+    tree.disconnect(fader, 0).unwrap();
+    tree.remove_node(wave).unwrap();
+    assert_eq!(tree.feedback_source(trails), None);
+    assert_eq!(tree.feedback_source(fader), None);
+    assert!(tree.set_feedback(trails, Some(wave)).is_err());
+}
+
+#[test]
+fn test_unconnected_nodes_are_described() {
+    let info = |name: &str| NodeInfo {
+        name: name.to_string(),
+        node_type: "wave".to_string(),
+        size: [1, 1],
+        format: NodeFormat::default(),
+        options: NodeOptions::new(),
+    };
+
+    let mut arena: Arena<Box<dyn TextureNode>> = Arena::new();
+    let root = arena.new_node(Box::new(DummyNode::default()));
+    let mut infos = HashMap::new();
+    infos.insert(root, info("root"));
+    let mut tree = TextureTree::from_edges(arena, HashMap::new(), root, infos).unwrap();
+
+    // e.g. added with /graph/add and not connected yet
+    let added = tree
+        .add_node(Box::new(DummyNode::default()), info("added"))
+        .unwrap();
+    tree.set_feedback(added, Some(root)).unwrap();
+    assert_eq!(tree.nodes().count(), 1);
+
+    let graph = GraphDescription::from_tree(&tree, [1, 1], NodeFormat::default());
+    let names: Vec<&str> = graph.nodes.iter().map(|n| n.name.as_str()).collect();
+    assert_eq!(names, vec!["root", "added"]);
+    assert_eq!(graph.nodes[1].feedback.as_deref(), Some("root"));
+}
+
+#[test]
+fn test_feedback_port_sampler() {
+    // set_samplers needs a device, skipped without a Vulkan adapter
+    let gpu = match HeadlessDevice::new(Features::ADDRESS_MODE_CLAMP_TO_BORDER) {
+        Some(gpu) => gpu,
+        None => return,
+    };
+
+    let samplers = Rc::new(RefCell::new(Vec::new()));
+    let mut arena: Arena<Box<dyn TextureNode>> = Arena::new();
+    let trails = arena.new_node(Box::new(FeedbackPortNode(samplers.clone())));
+    let mut tree = TextureTree::from_edges(arena, HashMap::new(), trails, HashMap::new()).unwrap();
+
+    let mirror = SamplerConfig {
+        address: SamplerAddress::Mirror,
+        ..SamplerConfig::default()
+    };
+    tree.set_sampler(&gpu.device, trails, "feedback", mirror)
+        .unwrap();
+    assert_eq!(*samplers.borrow(), vec![mirror]);
+    assert!(tree
+        .set_sampler(&gpu.device, trails, "input", mirror)
+        .is_err());
+}
+
+/// builds a tree of [DummyNode]s with a `/<name>/weight<i>` parameter per input,
+/// `nodes` are the names with their inputs, the first is the root
+fn build_weighted(
+    nodes: &[(&str, &[&str])],
+    cached: bool,
+    store: &mut ParameterStore,
+) -> (TextureTree, HashMap<String, NodeId>) {
+    let mut arena: Arena<Box<dyn TextureNode>> = Arena::new();
+    let mut ids = HashMap::new();
+    let mut infos = HashMap::new();
+    for &(name, inputs) in nodes {
+        let weights = (0..inputs.len())
+            .map(|i| ParameterEndpoint::new(1.0, format!("/{}/weight{}", name, i), store))
+            .collect();
+        let id = arena.new_node(Box::new(DummyNode { weights, cached }));
+        ids.insert(name.to_string(), id);
+        infos.insert(
+            id,
+            NodeInfo {
+                name: name.to_string(),
+                node_type: "dummy".to_string(),
+                size: [1, 1],
+                format: NodeFormat::default(),
+                options: NodeOptions::new(),
+            },
+        );
+    }
+    let inputs = nodes
+        .iter()
+        .map(|&(name, inputs)| (ids[name], inputs.iter().map(|&i| ids[i]).collect()))
+        .collect();
+
+    let mut tree = TextureTree::from_edges(arena, inputs, ids[nodes[0].0], infos).unwrap();
+    tree.register_controls(store);
+    (tree, ids)
+}
+
+fn set(store: &mut ParameterStore, addr: &str, value: Type) {
+    store.update(&Message {
+        addr: addr.to_string(),
+        args: Some(vec![value]),
+    });
+}
+
+#[test]
+fn test_fader_weight_zero_prunes_input() {
+    let mut store = ParameterStore::new();
+    let nodes: &[(&str, &[&str])] = &[
+        ("fader", &["a", "b"]),
+        ("a", &[]),
+        ("b", &["c"]),
+        ("c", &[]),
+    ];
+    let (mut tree, ids) = build_weighted(nodes, false, &mut store);
+
+    set(&mut store, "/fader/weight1", Type::Float(0.0));
+    assert_eq!(tree.schedule(&store), vec![ids["a"], ids["fader"]]);
+
+    set(&mut store, "/fader/weight1", Type::Float(0.5));
+    assert_eq!(tree.schedule(&store).len(), 4);
+}
+
+#[test]
+fn test_muted_subtree_is_skipped() {
+    let mut store = ParameterStore::new();
+    let nodes: &[(&str, &[&str])] = &[("mask", &["a", "b"]), ("a", &["c"]), ("b", &[]), ("c", &[])];
+    let (mut tree, ids) = build_weighted(nodes, false, &mut store);
+
+    set(&mut store, "/a/mute", Type::Bool(true));
+    assert_eq!(tree.schedule(&store), vec![ids["b"], ids["mask"]]);
+
+    // a bypassed node is skipped, its first input is still rendered
+    set(&mut store, "/a/mute", Type::Bool(false));
+    set(&mut store, "/a/bypass", Type::Bool(true));
+    assert_eq!(tree.schedule(&store), vec![ids["c"], ids["b"], ids["mask"]]);
+}
+
+#[test]
+fn test_parameter_change_invalidates_downstream() {
+    let mut store = ParameterStore::new();
+    let nodes: &[(&str, &[&str])] = &[
+        ("out", &["mid", "b"]),
+        ("mid", &["a"]),
+        ("a", &["src"]),
+        ("src", &[]),
+        ("b", &[]),
+    ];
+    let (mut tree, ids) = build_weighted(nodes, true, &mut store);
+
+    assert_eq!(tree.schedule(&store).len(), 5);
+    assert!(tree.schedule(&store).is_empty());
+
+    // the changed node and all nodes using it are rendered again, the others stay cached
+    set(&mut store, "/a/weight0", Type::Float(0.5));
+    assert_eq!(
+        tree.schedule(&store),
+        vec![ids["a"], ids["mid"], ids["out"]]
+    );
+    assert!(tree.schedule(&store).is_empty());
+}
+
+#[test]
+fn test_monitored_node_is_rendered() {
+    let mut store = ParameterStore::new();
+    let nodes: &[(&str, &[&str])] = &[("out", &["a"]), ("a", &[]), ("unused", &["b"]), ("b", &[])];
+    let (mut tree, ids) = build_weighted(nodes, false, &mut store);
+    assert_eq!(tree.schedule(&store), vec![ids["a"], ids["out"]]);
+
+    tree.set_monitored(Some(ids["unused"])).unwrap();
+    let scheduled = tree.schedule(&store);
+    assert!(scheduled.contains(&ids["unused"]) && scheduled.contains(&ids["b"]));
+
+    tree.remove_node(ids["unused"]).unwrap();
+    assert_eq!(tree.monitored(), None);
+    assert_eq!(tree.schedule(&store), vec![ids["a"], ids["out"]]);
+}
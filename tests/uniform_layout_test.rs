@@ -0,0 +1,100 @@
+use visgen_graph::{
+    check_uniform_layout, reflect_uniform_block, uniform_layout, UniformLayoutError, UniformMember,
+};
+
+/// compiled from `examples/shaders/shader.frag`,
+/// `uniform Data { vec4 color; float time; mat4 trans; }`
+fn example_shader() -> Vec<u32> {
+    std::fs::read("examples/shaders/frag.spv")
+        .unwrap()
+        .chunks_exact(4)
+        .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .collect()
+}
+
+#[repr(C, align(16))]
+#[derive(Clone, Copy)]
+struct Mat4([[f32; 4]; 4]);
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct Matching {
+    color: [f32; 4],
+    time: f32,
+    trans: Mat4,
+}
+uniform_layout!(Matching { color, time, trans });
+
+/// `color` is a vec3, so the following members are packed too tight
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct Packed {
+    color: [f32; 3],
+    time: f32,
+    trans: Mat4,
+}
+uniform_layout!(Packed { color, time, trans });
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct Missing {
+    color: [f32; 4],
+    time: f32,
+}
+uniform_layout!(Missing { color, time });
+
+fn member(name: &str, offset: usize, size: usize) -> UniformMember {
+    UniformMember {
+        name: name.to_string(),
+        offset,
+        size,
+    }
+}
+
+#[test]
+fn test_reflect_uniform_block() {
+    let spirv = example_shader();
+    let block = reflect_uniform_block(&spirv, 0, 0).unwrap().unwrap();
+    assert_eq!(
+        block,
+        vec![
+            member("color", 0, 16),
+            member("time", 16, 4),
+            member("trans", 32, 64)
+        ]
+    );
+
+    assert!(reflect_uniform_block(&spirv, 0, 1).unwrap().is_none());
+    assert!(matches!(
+        reflect_uniform_block(&[0, 1, 2], 0, 0),
+        Err(UniformLayoutError::InvalidSpirv(_))
+    ));
+    // an OpTypeStruct without operands is an error, not a panic
+    let truncated = [0x0723_0203, 0x0001_0000, 0, 1, 0, 1 << 16 | 30];
+    assert!(matches!(
+        reflect_uniform_block(&truncated, 0, 0),
+        Err(UniformLayoutError::InvalidSpirv(_))
+    ));
+}
+
+#[test]
+fn test_check_uniform_layout() {
+    let spirv = example_shader();
+    assert!(check_uniform_layout::<Matching>("frag", &spirv, 0, 0).is_ok());
+    // the shader does not use binding 1
+    assert!(check_uniform_layout::<Packed>("frag", &spirv, 0, 1).is_ok());
+
+    match check_uniform_layout::<Packed>("frag", &spirv, 0, 0) {
+        Err(UniformLayoutError::Mismatch(shader, _, members)) => {
+            assert_eq!(shader, "frag");
+            assert_eq!(members.len(), 3);
+            assert!(members[1].starts_with("'time' has offset 16 and size 4 in the shader"));
+        }
+        _ => panic!("layout mismatch not detected"),
+    }
+
+    let error = check_uniform_layout::<Missing>("frag", &spirv, 0, 0).unwrap_err();
+    assert!(error
+        .to_string()
+        .contains("'trans' at offset 32 is missing in the struct"));
+}